@@ -0,0 +1,58 @@
+pub mod agent {
+    use crate::{
+        agents::{
+            greedy_agent::greedy_agent::GreedyAgent,
+            hamiltonian_agent::hamiltonian_agent::HamiltonianAgent,
+            neural_agent::neural_agent::NeuralAgent,
+            path_finding_agent::path_finding_agent::PathFindingAgent,
+        },
+        game::game::{Direction, GameState},
+    };
+
+    pub const AGENT_NAMES: [&str; 3] = ["greedy", "path", "hamiltonian"];
+
+    pub trait Agent {
+        fn act(&mut self, state: &GameState) -> Direction;
+    }
+
+    // `name` is either one of AGENT_NAMES or the path of a trained network
+    pub fn create_agent(name: &str, grid_size: u32) -> Result<Box<dyn Agent>, String> {
+        match name {
+            "greedy" => Ok(Box::new(GreedyAgent)),
+            "path" => Ok(Box::<PathFindingAgent>::default()),
+            "hamiltonian" => match HamiltonianAgent::new(grid_size) {
+                Some(agent) => Ok(Box::new(agent)),
+                None => Err("the hamiltonian agent needs an even grid size".to_string()),
+            },
+            path => {
+                if !std::path::Path::new(path).is_file() {
+                    return Err(format!("{} is not an agent name or a network file", path));
+                }
+
+                Ok(Box::new(NeuralAgent::new_from_file(path)?))
+            }
+        }
+    }
+
+    pub fn get_neighbours(cell: (i32, i32)) -> [(Direction, (i32, i32)); 4] {
+        Direction::ALL.map(|direction| {
+            let vector = direction.to_vector();
+            (direction, (cell.0 + vector.0, cell.1 + vector.1))
+        })
+    }
+
+    // `to` must be next to `from`
+    pub fn direction_to(from: (i32, i32), to: (i32, i32)) -> Direction {
+        Direction::from_vector((to.0 - from.0, to.1 - from.1)).unwrap()
+    }
+
+    // the moves that don't kill the snake on the next turn, never includes the reversal
+    pub fn get_safe_moves(state: &GameState) -> Vec<(Direction, (i32, i32))> {
+        get_neighbours(state.get_head())
+            .into_iter()
+            .filter(|(direction, cell)| {
+                *direction != state.direction.opposite() && !state.is_deadly(*cell)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,21 @@
+pub mod greedy_agent {
+    use crate::{
+        agents::agent::agent::{get_safe_moves, Agent},
+        game::game::{Direction, GameState},
+    };
+
+    // moves to the safe cell closest to the apple without looking further than one move ahead
+    pub struct GreedyAgent;
+
+    impl Agent for GreedyAgent {
+        fn act(&mut self, state: &GameState) -> Direction {
+            get_safe_moves(state)
+                .into_iter()
+                .min_by_key(|(_, cell)| {
+                    cell.0.abs_diff(state.apple.0) + cell.1.abs_diff(state.apple.1)
+                })
+                .map(|(direction, _)| direction)
+                .unwrap_or(state.direction)
+        }
+    }
+}
@@ -0,0 +1,126 @@
+pub mod hamiltonian_agent {
+    use crate::{
+        agents::agent::agent::{direction_to, get_safe_moves, Agent},
+        game::game::{Direction, GameState},
+    };
+
+    // cells closer than this to the tail (along the cycle) are never used as a shortcut,
+    // so the snake has room to grow while it cuts through the cycle
+    const SHORTCUT_MARGIN: usize = 4;
+
+    // follows a cycle that visits every cell of the board, so it can never trap itself and
+    // always fills the board. while the snake is short it takes shortcuts towards the apple
+    // that don't break the order of the body along the cycle
+    pub struct HamiltonianAgent {
+        cycle: Vec<(i32, i32)>,
+        // position of every cell in the cycle, indexed by y * grid_size + x
+        cycle_index: Vec<usize>,
+        grid_size: i32,
+        reversed: bool,
+    }
+
+    impl HamiltonianAgent {
+        // a hamiltonian cycle only exists when the board has an even amount of cells
+        pub fn new(grid_size: u32) -> Option<HamiltonianAgent> {
+            if grid_size < 2 || !grid_size.is_multiple_of(2) {
+                return None;
+            }
+
+            let size = grid_size as i32;
+            let mut cycle = Vec::new();
+
+            // go right along the first row, zigzag through the rest of the board without the
+            // first column, and return up along the first column
+            for x in 0..size {
+                cycle.push((x, 0));
+            }
+
+            for y in 1..size {
+                if y % 2 == 1 {
+                    for x in (1..size).rev() {
+                        cycle.push((x, y));
+                    }
+                } else {
+                    for x in 1..size {
+                        cycle.push((x, y));
+                    }
+                }
+            }
+
+            for y in (1..size).rev() {
+                cycle.push((0, y));
+            }
+
+            let mut cycle_index = vec![0; cycle.len()];
+
+            for (i, cell) in cycle.iter().enumerate() {
+                cycle_index[(cell.1 * size + cell.0) as usize] = i;
+            }
+
+            Some(HamiltonianAgent {
+                cycle,
+                cycle_index,
+                grid_size: size,
+                reversed: false,
+            })
+        }
+
+        fn get_index(&self, cell: (i32, i32), reversed: bool) -> usize {
+            let index = self.cycle_index[(cell.1 * self.grid_size + cell.0) as usize];
+
+            if reversed {
+                self.cycle.len() - 1 - index
+            } else {
+                index
+            }
+        }
+
+        // how many steps along the cycle it takes to get from `from` to `to`
+        fn get_distance(&self, from: (i32, i32), to: (i32, i32), reversed: bool) -> usize {
+            let length = self.cycle.len();
+            (self.get_index(to, reversed) + length - self.get_index(from, reversed)) % length
+        }
+    }
+
+    impl Agent for HamiltonianAgent {
+        fn act(&mut self, state: &GameState) -> Direction {
+            let head = state.get_head();
+            let tail = state.snake[state.snake.len() - 1];
+
+            // walk the cycle in whichever direction the snake is already facing, after a
+            // shortcut the neck isn't next to the head on the cycle so the last one is kept
+            match self.get_distance(state.snake[1], head, false) {
+                1 => self.reversed = false,
+                distance if distance == self.cycle.len() - 1 => self.reversed = true,
+                _ => {}
+            }
+            let reversed = self.reversed;
+
+            let length = self.cycle.len();
+            let mut next = {
+                let index = (self.get_index(head, reversed) + 1) % length;
+                self.cycle[if reversed { length - 1 - index } else { index }]
+            };
+
+            if state.snake.len() < length / 2 {
+                let apple_distance = self.get_distance(head, state.apple, reversed);
+                let tail_distance = self.get_distance(head, tail, reversed);
+                let mut best_distance = 1;
+
+                for (_, cell) in get_safe_moves(state) {
+                    let distance = self.get_distance(head, cell, reversed);
+
+                    if distance > best_distance
+                        && distance <= apple_distance
+                        && distance + SHORTCUT_MARGIN < tail_distance
+                    {
+                        best_distance = distance;
+                        next = cell;
+                    }
+                }
+            }
+
+            direction_to(head, next)
+        }
+    }
+}
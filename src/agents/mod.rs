@@ -0,0 +1,5 @@
+pub mod agent;
+pub mod greedy_agent;
+pub mod hamiltonian_agent;
+pub mod neural_agent;
+pub mod path_finding_agent;
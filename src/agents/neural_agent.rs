@@ -0,0 +1,255 @@
+pub mod neural_agent {
+    use crate::{
+        agents::agent::agent::Agent,
+        ai::{
+            actions::actions::{get_relative_state, ActionSpace, RelativeEncoder},
+            activation::activation::ActivationFunction,
+            model::model::{write_model, Model},
+            neural_network::neural_network::{BatchBuffers, NeuralNetwork},
+            observation::observation::{create_encoder, Observation, ObservationEncoder, Ray},
+        },
+        game::game::{Direction, GameState},
+    };
+    use ndarray::{Array1, ArrayBase, ArrayView2, Data, Ix1};
+    use std::sync::Arc;
+
+    pub const DEFAULT_LAYOUT: &str = "40 40";
+
+    // the layers of a new network between the observation and the outputs, the size of the
+    // output layer comes from the action space
+    #[derive(Debug, Clone)]
+    pub struct NetworkLayout {
+        pub hidden: Vec<(u32, ActivationFunction)>,
+        pub output: ActivationFunction,
+    }
+
+    // the sizes of the hidden layers separated by spaces, each one can have :activation after
+    // it and output:activation sets the output layer, like "64:relu 64:relu output:softmax".
+    // layers without an activation are sigmoid
+    pub fn parse_layout(text: &str) -> Result<NetworkLayout, String> {
+        let mut layout = NetworkLayout {
+            hidden: Vec::new(),
+            output: ActivationFunction::Sigmoid,
+        };
+
+        for part in text.split_whitespace() {
+            let (size, activation) = match part.split_once(':') {
+                Some((size, name)) => {
+                    let activation = ActivationFunction::from_name(name).ok_or(format!(
+                        "unknown activation {}, use one of {}",
+                        name,
+                        get_activation_names().join(", ")
+                    ))?;
+                    (size, activation)
+                }
+                None => (part, ActivationFunction::Sigmoid),
+            };
+
+            if size == "output" {
+                layout.output = activation;
+            } else {
+                let size = size
+                    .parse()
+                    .ok()
+                    .filter(|size| *size > 0)
+                    .ok_or(format!("invalid layer size {}", size))?;
+                layout.hidden.push((size, activation));
+            }
+        }
+
+        Ok(layout)
+    }
+
+    pub fn get_activation_names() -> Vec<&'static str> {
+        ActivationFunction::ALL
+            .iter()
+            .map(|function| function.get_name())
+            .collect()
+    }
+
+    #[derive(Clone)]
+    pub struct NeuralAgent {
+        neural_network: NeuralNetwork,
+        encoder: Arc<dyn ObservationEncoder>,
+        action_space: ActionSpace,
+    }
+
+    impl NeuralAgent {
+        pub fn new(
+            neural_network: NeuralNetwork,
+            encoder: Arc<dyn ObservationEncoder>,
+            action_space: ActionSpace,
+        ) -> NeuralAgent {
+            NeuralAgent {
+                neural_network,
+                encoder,
+                action_space,
+            }
+        }
+
+        // random weights, sized for the encoder and the action space
+        pub fn new_random(
+            encoder: Arc<dyn ObservationEncoder>,
+            action_space: ActionSpace,
+            layout: &NetworkLayout,
+        ) -> NeuralAgent {
+            let mut shape = vec![encoder.size() as u32];
+            shape.extend(layout.hidden.iter().map(|(size, _)| size));
+            shape.push(action_space.get_output_count() as u32);
+
+            let mut activations: Vec<ActivationFunction> = layout
+                .hidden
+                .iter()
+                .map(|(_, activation)| *activation)
+                .collect();
+            activations.push(layout.output);
+
+            NeuralAgent::new(
+                NeuralNetwork::new(shape, activations),
+                encoder,
+                action_space,
+            )
+        }
+
+        // the same encoder, action space and layers with new random weights
+        pub fn new_random_like(&self) -> NeuralAgent {
+            NeuralAgent::new(
+                self.neural_network.new_random_like(),
+                self.encoder.clone(),
+                self.action_space,
+            )
+        }
+
+        pub fn new_from_file(path: &str) -> Result<NeuralAgent, String> {
+            let model = Model::new_from_file(path)?;
+            let encoder = create_encoder(&model.encoder)?;
+            let shape = model.network.get_shape();
+
+            if shape[0] as usize != encoder.size() {
+                return Err(format!(
+                    "{} has {} inputs but the {} encoder gives {}",
+                    path,
+                    shape[0],
+                    model.encoder,
+                    encoder.size()
+                ));
+            }
+
+            if shape[shape.len() - 1] as usize != model.action_space.get_output_count() {
+                return Err(format!(
+                    "{} doesn't have {} outputs for the {} action space",
+                    path,
+                    model.action_space.get_output_count(),
+                    model.action_space.get_name()
+                ));
+            }
+
+            Ok(NeuralAgent::new(
+                model.network,
+                Arc::from(encoder),
+                model.action_space,
+            ))
+        }
+
+        pub fn write_to_file(&self, path: &str) -> std::io::Result<()> {
+            write_model(
+                path,
+                &self.neural_network,
+                &self.encoder.get_id(),
+                self.action_space,
+            )
+        }
+
+        pub fn get_action_space(&self) -> ActionSpace {
+            self.action_space
+        }
+
+        // a relative network sees the board turned so the snake faces up
+        fn encode(&self, state: &GameState) -> Observation {
+            match self.action_space {
+                ActionSpace::Absolute => self.encoder.encode(state),
+                ActionSpace::Relative => self.encoder.encode(&get_relative_state(state)),
+            }
+        }
+
+        pub fn mutate(&mut self, mutation_percent: f64) {
+            self.neural_network.mutate(mutation_percent);
+        }
+
+        pub fn get_neural_network(&self) -> &NeuralNetwork {
+            &self.neural_network
+        }
+
+        // one output for every action, see ActionSpace::get_output_names
+        pub fn get_outputs(&self, state: &GameState) -> Array1<f64> {
+            let input = self.encode(state);
+            self.neural_network.feed_forward(&input)
+        }
+
+        // the values of every layer of the network for the state, see
+        // NeuralNetwork::get_activations
+        pub fn get_activations(&self, state: &GameState) -> Vec<Array1<f64>> {
+            let input = self.encode(state);
+            self.neural_network.get_activations(&input)
+        }
+
+        // the rays of the encoder for the sensor overlay, on the board as it is drawn
+        pub fn get_sensor_rays(&self, state: &GameState) -> Vec<Ray> {
+            self.encoder.get_sensor_rays(state)
+        }
+
+        // what the network sees, for a SnakeEnvironment
+        pub fn get_observation_encoder(&self) -> Box<dyn ObservationEncoder> {
+            match self.action_space {
+                ActionSpace::Absolute => Box::new(self.encoder.clone()),
+                ActionSpace::Relative => Box::new(RelativeEncoder {
+                    encoder: self.encoder.clone(),
+                }),
+            }
+        }
+
+        // what act would do for every row of `observations` (see get_observation_encoder)
+        // when the snakes go `directions`, with a single pass through the network. `buffers`
+        // are reused between calls
+        pub fn act_batch(
+            &self,
+            observations: ArrayView2<f64>,
+            directions: &[Direction],
+            buffers: &mut BatchBuffers,
+        ) -> Vec<Direction> {
+            let outputs = self
+                .neural_network
+                .feed_forward_batch(observations, buffers);
+
+            outputs
+                .rows()
+                .into_iter()
+                .zip(directions)
+                .map(|(outputs, direction)| {
+                    self.action_space
+                        .get_direction(get_choice(&outputs), *direction)
+                })
+                .collect()
+        }
+    }
+
+    // the index of the biggest output, the first one wins a tie
+    pub fn get_choice(outputs: &ArrayBase<impl Data<Elem = f64>, Ix1>) -> usize {
+        let mut max_index = 0;
+
+        for i in 1..outputs.len() {
+            if outputs[i] > outputs[max_index] {
+                max_index = i;
+            }
+        }
+
+        max_index
+    }
+
+    impl Agent for NeuralAgent {
+        fn act(&mut self, state: &GameState) -> Direction {
+            let choice = get_choice(&self.get_outputs(state));
+            self.action_space.get_direction(choice, state.direction)
+        }
+    }
+}
@@ -0,0 +1,164 @@
+pub mod path_finding_agent {
+    use std::collections::VecDeque;
+
+    use crate::{
+        agents::agent::agent::{direction_to, get_neighbours, get_safe_moves, Agent},
+        game::game::{Direction, GameState},
+    };
+
+    struct Grid {
+        size: i32,
+        blocked: Vec<bool>,
+    }
+
+    impl Grid {
+        // the tail is left free because it moves away on the next turn, the neck is always
+        // blocked so a path never starts with a reversal
+        fn new(grid_size: u32, snake: &[(i32, i32)]) -> Grid {
+            let size = grid_size as i32;
+            let mut blocked = vec![false; (size * size) as usize];
+
+            for cell in snake.iter().take((snake.len() - 1).max(2)) {
+                blocked[(cell.1 * size + cell.0) as usize] = true;
+            }
+
+            Grid { size, blocked }
+        }
+
+        fn index(&self, cell: (i32, i32)) -> usize {
+            (cell.1 * self.size + cell.0) as usize
+        }
+
+        fn is_free(&self, cell: (i32, i32)) -> bool {
+            cell.0 >= 0
+                && cell.0 < self.size
+                && cell.1 >= 0
+                && cell.1 < self.size
+                && !self.blocked[self.index(cell)]
+        }
+
+        // shortest path from `from` to `to` using bfs, without `from` itself
+        fn find_path(&self, from: (i32, i32), to: (i32, i32)) -> Option<Vec<(i32, i32)>> {
+            let mut previous: Vec<Option<(i32, i32)>> = vec![None; self.blocked.len()];
+            let mut queue = VecDeque::from([from]);
+            previous[self.index(from)] = Some(from);
+
+            while let Some(cell) = queue.pop_front() {
+                if cell == to {
+                    let mut path = vec![cell];
+
+                    while let Some(last) = previous[self.index(path[path.len() - 1])] {
+                        if last == from {
+                            break;
+                        }
+                        path.push(last);
+                    }
+
+                    path.reverse();
+                    return Some(path);
+                }
+
+                for (_, next) in get_neighbours(cell) {
+                    if self.is_free(next) && previous[self.index(next)].is_none() {
+                        previous[self.index(next)] = Some(cell);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            None
+        }
+
+        fn count_reachable(&self, from: (i32, i32)) -> usize {
+            let mut visited = vec![false; self.blocked.len()];
+            let mut queue = VecDeque::from([from]);
+            visited[self.index(from)] = true;
+            let mut count = 0;
+
+            while let Some(cell) = queue.pop_front() {
+                count += 1;
+
+                for (_, next) in get_neighbours(cell) {
+                    if self.is_free(next) && !visited[self.index(next)] {
+                        visited[self.index(next)] = true;
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            count
+        }
+    }
+
+    fn follow_path(
+        snake: &[(i32, i32)],
+        path: &[(i32, i32)],
+        apple: (i32, i32),
+    ) -> Vec<(i32, i32)> {
+        let mut snake = snake.to_vec();
+
+        for cell in path {
+            snake.insert(0, *cell);
+
+            if *cell != apple {
+                snake.pop();
+            }
+        }
+
+        snake
+    }
+
+    // takes the shortest path to the apple, but only if the snake can still reach its own tail
+    // after eating it, otherwise it follows its tail until the path becomes safe. following the
+    // tail can go around the same loop forever, so after a while without an apple it takes
+    // the path anyway
+    #[derive(Default)]
+    pub struct PathFindingAgent {
+        // the length of the snake on the last move, it changes when an apple is eaten or a new
+        // game starts
+        length: usize,
+        turns_without_apple: usize,
+    }
+
+    impl Agent for PathFindingAgent {
+        fn act(&mut self, state: &GameState) -> Direction {
+            if state.snake.len() != self.length {
+                self.length = state.snake.len();
+                self.turns_without_apple = 0;
+            }
+            self.turns_without_apple += 1;
+
+            let head = state.get_head();
+            let grid = Grid::new(state.grid_size, &state.snake);
+
+            if let Some(path) = grid.find_path(head, state.apple) {
+                let snake_after = follow_path(&state.snake, &path, state.apple);
+                let tail_after = snake_after[snake_after.len() - 1];
+
+                // one full loop around the tail didn't make the path safe, it won't get better.
+                // a long snake only waits a few lengths of the board so it doesn't starve
+                let impatient =
+                    self.turns_without_apple > state.snake.len().min(state.grid_size as usize * 4);
+
+                if impatient
+                    || Grid::new(state.grid_size, &snake_after)
+                        .find_path(snake_after[0], tail_after)
+                        .is_some()
+                {
+                    return direction_to(head, path[0]);
+                }
+            }
+
+            if let Some(path) = grid.find_path(head, state.snake[state.snake.len() - 1]) {
+                return direction_to(head, path[0]);
+            }
+
+            // no safe plan, stay in the biggest open area as long as possible
+            get_safe_moves(state)
+                .into_iter()
+                .max_by_key(|(_, cell)| grid.count_reachable(*cell))
+                .map(|(direction, _)| direction)
+                .unwrap_or(state.direction)
+        }
+    }
+}
@@ -0,0 +1,158 @@
+pub mod actions {
+    use std::sync::Arc;
+
+    use crate::{
+        ai::observation::observation::{Observation, ObservationEncoder},
+        game::game::{Direction, GameState},
+    };
+
+    // what the outputs of a network mean. absolute has one output per direction, one of them
+    // is always the reversal that the game ignores. relative has turn left, go straight and
+    // turn right, and the network sees the board turned so the snake always faces up, so what
+    // it learns works the same in every direction
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ActionSpace {
+        Absolute,
+        Relative,
+    }
+
+    impl ActionSpace {
+        pub const ALL: [ActionSpace; 2] = [ActionSpace::Absolute, ActionSpace::Relative];
+
+        pub fn get_name(self) -> &'static str {
+            match self {
+                ActionSpace::Absolute => "absolute",
+                ActionSpace::Relative => "relative",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<ActionSpace> {
+            ActionSpace::ALL
+                .into_iter()
+                .find(|action_space| action_space.get_name() == name)
+        }
+
+        // the name of every output, in order
+        pub fn get_output_names(self) -> &'static [&'static str] {
+            match self {
+                ActionSpace::Absolute => &["up", "right", "down", "left"],
+                ActionSpace::Relative => &["left", "straight", "right"],
+            }
+        }
+
+        pub fn get_output_count(self) -> usize {
+            self.get_output_names().len()
+        }
+
+        // the direction to go for the output at `index` when the snake goes `direction`
+        pub fn get_direction(self, index: usize, direction: Direction) -> Direction {
+            match self {
+                ActionSpace::Absolute => Direction::ALL[index],
+                ActionSpace::Relative => match index {
+                    0 => turn_clockwise(direction, 3),
+                    1 => direction,
+                    _ => turn_clockwise(direction, 1),
+                },
+            }
+        }
+    }
+
+    fn turn_clockwise(direction: Direction, turns: usize) -> Direction {
+        let index = Direction::ALL
+            .iter()
+            .position(|other| *other == direction)
+            .unwrap();
+
+        // Direction::ALL goes clockwise
+        Direction::ALL[(index + turns) % Direction::ALL.len()]
+    }
+
+    // the board turned around its center so the snake faces up
+    pub fn get_relative_state(state: &GameState) -> GameState {
+        let turns = match state.direction {
+            Direction::Up => 0,
+            Direction::Right => 3,
+            Direction::Down => 2,
+            Direction::Left => 1,
+        };
+
+        let last = state.grid_size as i32 - 1;
+        let rotate = |cell: (i32, i32)| (0..turns).fold(cell, |(x, y), _| (last - y, x));
+
+        GameState {
+            grid_size: state.grid_size,
+            snake: state.snake.iter().map(|part| rotate(*part)).collect(),
+            direction: Direction::Up,
+            apple: rotate(state.apple),
+        }
+    }
+
+    // what a relative network sees, the board turned so the snake faces up and then encoded
+    // with `encoder`. the id is the one of `encoder` since the action space is saved apart
+    pub struct RelativeEncoder {
+        pub encoder: Arc<dyn ObservationEncoder>,
+    }
+
+    impl ObservationEncoder for RelativeEncoder {
+        fn size(&self) -> usize {
+            self.encoder.size()
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            self.encoder.encode(&get_relative_state(state))
+        }
+
+        fn get_id(&self) -> String {
+            self.encoder.get_id()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn relative_state_faces_up() {
+            for direction in Direction::ALL {
+                let (x, y) = direction.to_vector();
+                // the apple is 2 cells in front of the head, the neck right behind it
+                let state = GameState {
+                    grid_size: 10,
+                    snake: vec![(3, 4), (3 - x, 4 - y)],
+                    direction,
+                    apple: (3 + x * 2, 4 + y * 2),
+                };
+
+                let relative = get_relative_state(&state);
+                let head = relative.snake[0];
+                let neck = relative.snake[1];
+
+                assert_eq!(relative.direction, Direction::Up);
+                assert_eq!(relative.grid_size, 10);
+                assert_eq!((neck.0, neck.1 - 1), head, "{:?}", direction);
+                assert_eq!(relative.apple, (head.0, head.1 - 2), "{:?}", direction);
+                assert!(relative.is_inside(head) && relative.is_inside(relative.apple));
+            }
+        }
+
+        #[test]
+        fn relative_actions_turn() {
+            for direction in Direction::ALL {
+                let get_direction = |index| ActionSpace::Relative.get_direction(index, direction);
+
+                assert_eq!(get_direction(0), turn_clockwise(direction, 3));
+                assert_eq!(get_direction(1), direction);
+                assert_eq!(get_direction(2), turn_clockwise(direction, 1));
+            }
+
+            assert_eq!(
+                ActionSpace::Relative.get_direction(0, Direction::Up),
+                Direction::Left
+            );
+            assert_eq!(
+                ActionSpace::Relative.get_direction(2, Direction::Up),
+                Direction::Right
+            );
+        }
+    }
+}
@@ -0,0 +1,66 @@
+pub mod activation {
+    use ndarray::ArrayViewMut1;
+
+    const LEAKY_RELU_SLOPE: f64 = 0.01;
+
+    // what a layer does to its values after the weights and biases. sigmoid is the only one
+    // networks had before, so it is the default
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ActivationFunction {
+        Sigmoid,
+        Relu,
+        LeakyRelu,
+        Tanh,
+        Linear,
+        Softmax,
+    }
+
+    impl ActivationFunction {
+        pub const ALL: [ActivationFunction; 6] = [
+            ActivationFunction::Sigmoid,
+            ActivationFunction::Relu,
+            ActivationFunction::LeakyRelu,
+            ActivationFunction::Tanh,
+            ActivationFunction::Linear,
+            ActivationFunction::Softmax,
+        ];
+
+        pub fn get_name(self) -> &'static str {
+            match self {
+                ActivationFunction::Sigmoid => "sigmoid",
+                ActivationFunction::Relu => "relu",
+                ActivationFunction::LeakyRelu => "leaky_relu",
+                ActivationFunction::Tanh => "tanh",
+                ActivationFunction::Linear => "linear",
+                ActivationFunction::Softmax => "softmax",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<ActivationFunction> {
+            ActivationFunction::ALL
+                .into_iter()
+                .find(|function| function.get_name() == name)
+        }
+
+        // softmax depends on every value of the layer so the whole layer is changed at once
+        pub fn apply(self, mut values: ArrayViewMut1<f64>) {
+            match self {
+                ActivationFunction::Sigmoid => values.mapv_inplace(|x| 1.0 / (1.0 + (-x).exp())),
+                ActivationFunction::Relu => values.mapv_inplace(|x| x.max(0.0)),
+                ActivationFunction::LeakyRelu => {
+                    values.mapv_inplace(|x| if x > 0.0 { x } else { x * LEAKY_RELU_SLOPE })
+                }
+                ActivationFunction::Tanh => values.mapv_inplace(f64::tanh),
+                ActivationFunction::Linear => {}
+                ActivationFunction::Softmax => {
+                    // subtracting the biggest value keeps exp from overflowing
+                    let max = values.fold(f64::NEG_INFINITY, |max, x| max.max(*x));
+                    values.mapv_inplace(|x| (x - max).exp());
+
+                    let sum = values.sum();
+                    values.mapv_inplace(|x| x / sum);
+                }
+            }
+        }
+    }
+}
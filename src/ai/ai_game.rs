@@ -1,188 +1,177 @@
 pub mod ai_game {
+    use crate::agents::{agent::agent::Agent, neural_agent::neural_agent::NeuralAgent};
+    use crate::constants::constants::{NETWORK_PANEL_WIDTH, TURBO_TIME_PER_UPDATE};
     use crate::game::game::*;
-    use crate::{
-        ai::neural_network::neural_network::NeuralNetwork, constants::constants::GRID_SIZE,
-    };
+    use crate::key_bindings::key_bindings::Command;
+    use crate::network_view::network_view::render_network;
+    use crate::sensors::sensors::render_rays;
+    use crate::theme::theme::Theme;
     use ndarray::Array1;
     use opengl_graphics::GlGraphics;
-    use piston::{keyboard, RenderArgs, UpdateArgs};
-
-    fn find_in_direction(
-        game_map: &Vec<Vec<MapItem>>,
-        head: (i32, i32),
-        direction: (i32, i32),
-    ) -> (u32, u32, u32) {
-        let mut snake_distance = 0;
-        let mut food_distance = 0;
-        let mut wall_distance = 0;
-
-        let mut current = head;
-
-        loop {
-            current = (current.0 + direction.0, current.1 + direction.1);
-            wall_distance += 1;
-
-            if current.0 < 0
-                || current.0 >= GRID_SIZE as i32
-                || current.1 < 0
-                || current.1 >= GRID_SIZE as i32
-            {
-                break;
-            }
+    use piston::{RenderArgs, UpdateArgs};
+    use std::time::{Duration, Instant};
 
-            match game_map[current.1 as usize][current.0 as usize] {
-                MapItem::Apple => {
-                    food_distance = wall_distance;
-                }
-                MapItem::Snake => {
-                    if snake_distance == 0 {
-                        snake_distance = wall_distance;
-                    }
-                }
-                _ => {}
-            }
+    // how good a finished game was for training
+    pub fn get_fitness(score: u32, turns: u32) -> f64 {
+        if score == 0 {
+            return 0.0;
         }
 
-        (snake_distance, food_distance, wall_distance)
+        if score <= 10 {
+            return (1 << (score * 2)) as f64 * (turns as f64) / 100.0;
+        }
+
+        (1 << 20) as f64 * (score - 9) as f64 * (turns as f64) / 100.0
     }
 
-    fn get_input(game_map: Vec<Vec<MapItem>>, head: (i32, i32)) -> Array1<f64> {
-        const DIRECTIONS: [(i32, i32); 8] = [
-            (0, 1),
-            (1, 1),
-            (1, 0),
-            (1, -1),
-            (0, -1),
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-        ];
-
-        let mut input = Array1::zeros(24);
-
-        for i in 0..DIRECTIONS.len() {
-            let (snake_distance, food_distance, wall_distance) =
-                find_in_direction(&game_map, head, DIRECTIONS[i]);
-
-            input[0 + i * 3] = if snake_distance == 0 {
-                0.0
+    pub struct AiGame {
+        game: Game,
+        agent: NeuralAgent,
+        // moves as fast as possible, only the last state of every frame is drawn
+        turbo: bool,
+        // draws what the network sees and shows what it outputs
+        show_sensors: bool,
+        // draws the network next to the board
+        show_network: bool,
+    }
+
+    impl AiGame {
+        pub fn new_from(gl: Option<GlGraphics>, config: GameConfig, agent: &NeuralAgent) -> AiGame {
+            AiGame {
+                game: Game::new_with(gl, config, rand::random()),
+                agent: agent.clone(),
+                turbo: false,
+                show_sensors: false,
+                show_network: false,
+            }
+        }
+
+        pub fn mutate(&mut self, mutation_percent: f64) {
+            self.agent.mutate(mutation_percent);
+        }
+
+        // a smooth game glides between cells instead of jumping on every move, except in turbo
+        pub fn render(&mut self, args: &RenderArgs, theme: &Theme, smooth: bool) {
+            let progress = if smooth && !self.turbo {
+                self.game.get_move_progress()
             } else {
-                //1.0 / snake_distance as f64
                 1.0
             };
 
-            input[1 + i * 3] = if food_distance == 0 {
-                0.0
+            let panel_width = if self.show_network {
+                NETWORK_PANEL_WIDTH.min(args.window_size[0] / 2.0)
             } else {
-                //1.0 / food_distance as f64
-                1.0
+                0.0
             };
+            self.game.set_panel_width(panel_width);
 
-            input[2 + i * 3] = 1.0 / wall_distance as f64;
-        }
+            self.game.render(args, theme, progress);
 
-        input
-    }
+            if self.show_sensors {
+                let state = self.game.get_state();
+                let rays = self.agent.get_sensor_rays(&state);
+                let layout = self.game.get_board_layout(args);
+                let gl = self.game.gl.as_mut().unwrap();
+                render_rays(args, gl, layout, &state, &rays, theme);
+            }
 
-    pub struct AiGame {
-        game: Game,
-        neural_network: NeuralNetwork,
-    }
+            if self.show_network {
+                let [width, height] = args.window_size;
+                let area = [width - panel_width, 0.0, panel_width, height];
+                let activations = self.agent.get_activations(&self.game.get_state());
+                let network = self.agent.get_neural_network();
 
-    impl AiGame {
-        pub fn new(gl: Option<GlGraphics>) -> AiGame {
-            AiGame {
-                game: Game::new(gl),
-                neural_network: NeuralNetwork::new(vec![24, 40, 40, 4]),
+                let gl = self.game.gl.as_mut().unwrap();
+                render_network(args, gl, area, network, &activations, theme);
             }
         }
 
-        pub fn new_from(gl: Option<GlGraphics>, neural_network: &NeuralNetwork) -> AiGame {
-            AiGame {
-                game: Game::new(gl),
-                neural_network: neural_network.clone(),
+        // pause, speed up / down and restart work like in a human game, right makes a single
+        // move while paused, turbo toggles turbo mode, sensors the sensor overlay and network the
+        // network panel, the directions don't steer the ai
+        pub fn handle_command(&mut self, command: Command) {
+            match command {
+                Command::Pause | Command::SpeedUp | Command::SpeedDown | Command::Restart => {
+                    self.game.handle_command(command)
+                }
+                Command::Right if self.game.is_paused() => self.step(),
+                Command::Turbo => self.turbo = !self.turbo,
+                Command::Sensors => self.show_sensors = !self.show_sensors,
+                Command::Network => self.show_network = !self.show_network,
+                _ => {}
             }
         }
 
-        pub fn mutate(&mut self, mutation_percent: f64) {
-            self.neural_network.mutate(mutation_percent);
-        }
+        // one move of the agent, whatever the timer says
+        pub fn step(&mut self) {
+            if self.game.is_over() {
+                return;
+            }
 
-        pub fn render(&mut self, args: &RenderArgs) {
-            self.game.render(args);
+            let direction = self.agent.act(&self.game.get_state());
+            self.game.set_direction(direction);
+            self.game.tick();
         }
 
         pub fn update(&mut self, args: &UpdateArgs) {
-            if self.game.is_next_update_move(args.dt) {
-                let input = get_input(self.game.get_map(), self.game.get_snake_head());
-
-                // for i in 0..GRID_SIZE {
-                //     for j in 0..GRID_SIZE {
-                //         match self.game.get_map()[i as usize][j as usize] {
-                //             MapItem::Empty => print!("."),
-                //             MapItem::Snake => print!("S"),
-                //             MapItem::Apple => print!("A"),
-                //         }
-                //     }
-                //     println!();
-                // }
-
-                // for i in 0..input.len() {
-                //     println!("{}: {} ", i, input[i]);
-                // }
-
-                let output = self.neural_network.feed_forward(&input);
-
-                let mut max_index = 0;
-
-                for i in 1..output.len() {
-                    if output[i] > output[max_index] {
-                        max_index = i;
+            if self.turbo {
+                if !self.game.is_paused() {
+                    let start = Instant::now();
+                    let time = Duration::from_secs_f64(TURBO_TIME_PER_UPDATE);
+
+                    while !self.game.is_over() && start.elapsed() < time {
+                        self.step();
                     }
                 }
 
-                //println!("{} {} {} {}", output[0], output[1], output[2], output[3]);
+                return;
+            }
 
-                match max_index {
-                    0 => self.game.handle_input(keyboard::Key::Up),
-                    1 => self.game.handle_input(keyboard::Key::Right),
-                    2 => self.game.handle_input(keyboard::Key::Down),
-                    3 => self.game.handle_input(keyboard::Key::Left),
-                    _ => {}
-                }
+            if !self.game.is_over()
+                && !self.game.is_paused()
+                && self.game.is_next_update_move(args.dt)
+            {
+                let direction = self.agent.act(&self.game.get_state());
+                self.game.set_direction(direction);
             }
 
             self.game.update(args);
         }
 
-        pub fn get_game(&self) -> &Game {
-            &self.game
+        pub fn is_turbo(&self) -> bool {
+            self.turbo
         }
 
-        pub fn calc_fitness(&self) -> f64 {
-            if self.game.get_score() == 0 || self.game.get_turns() == 22 {
-                return 0.0;
-            }
+        pub fn is_showing_sensors(&self) -> bool {
+            self.show_sensors
+        }
 
-            if self.game.get_score() <= 10 {
-                return (1 << (self.game.get_score() * 2)) as f64 * (self.game.get_turns() as f64)
-                    / 100.0;
-            }
+        // what the network outputs for the current state, see ActionSpace::get_output_names
+        pub fn get_outputs(&self) -> Array1<f64> {
+            self.agent.get_outputs(&self.game.get_state())
+        }
+
+        pub fn get_game(&self) -> &Game {
+            &self.game
+        }
 
-            (1 << 20) as f64 * (self.game.get_score() - 9) as f64 * (self.game.get_turns() as f64)
-                / 100.0
+        pub fn get_game_mut(&mut self) -> &mut Game {
+            &mut self.game
         }
 
-        pub fn get_neural_network(&self) -> &NeuralNetwork {
-            &self.neural_network
+        pub fn get_agent(&self) -> &NeuralAgent {
+            &self.agent
         }
     }
 
     impl Clone for AiGame {
         fn clone(&self) -> Self {
             AiGame {
-                game: Game::new(None),
-                neural_network: self.neural_network.clone(),
+                game: Game::new_with(None, *self.game.get_config(), rand::random()),
+                agent: self.agent.clone(),
+                turbo: false,
+                show_sensors: false,
+                show_network: false,
             }
         }
     }
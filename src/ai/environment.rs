@@ -0,0 +1,351 @@
+pub mod environment {
+    use ndarray::{Array2, ArrayView2};
+
+    use crate::{
+        ai::observation::observation::{BinaryVisionEncoder, Observation, ObservationEncoder},
+        game::game::{Direction, Game, GameConfig, TerminalState},
+    };
+
+    pub type Action = Direction;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct StepInfo {
+        pub score: u32,
+        pub turns: u32,
+        pub terminal_state: Option<TerminalState>,
+    }
+
+    // everything a reward function needs to know about a single step
+    #[derive(Debug, Clone, Copy)]
+    pub struct Transition {
+        pub ate_apple: bool,
+        pub died: bool,
+        pub apple_distance_before: u32,
+        pub apple_distance_after: u32,
+    }
+
+    pub trait RewardFunction {
+        fn reward(&self, transition: &Transition) -> f64;
+    }
+
+    // +apple for every apple eaten and -death when the snake dies
+    pub struct AppleReward {
+        pub apple: f64,
+        pub death: f64,
+    }
+
+    impl Default for AppleReward {
+        fn default() -> Self {
+            AppleReward {
+                apple: 1.0,
+                death: 1.0,
+            }
+        }
+    }
+
+    impl RewardFunction for AppleReward {
+        fn reward(&self, transition: &Transition) -> f64 {
+            if transition.died {
+                -self.death
+            } else if transition.ate_apple {
+                self.apple
+            } else {
+                0.0
+            }
+        }
+    }
+
+    // apple reward plus a small bonus for moving towards the apple and penalty for moving away
+    pub struct DistanceReward {
+        pub apple_reward: AppleReward,
+        pub step: f64,
+    }
+
+    impl Default for DistanceReward {
+        fn default() -> Self {
+            DistanceReward {
+                apple_reward: AppleReward::default(),
+                step: 0.01,
+            }
+        }
+    }
+
+    impl RewardFunction for DistanceReward {
+        fn reward(&self, transition: &Transition) -> f64 {
+            let reward = self.apple_reward.reward(transition);
+
+            if transition.died || transition.ate_apple {
+                reward
+            } else if transition.apple_distance_after < transition.apple_distance_before {
+                reward + self.step
+            } else {
+                reward - self.step
+            }
+        }
+    }
+
+    pub const REWARD_NAMES: [&str; 2] = ["apple", "distance"];
+
+    pub fn create_reward_function(name: &str) -> Result<Box<dyn RewardFunction>, String> {
+        match name {
+            "apple" => Ok(Box::new(AppleReward::default())),
+            "distance" => Ok(Box::new(DistanceReward::default())),
+            _ => Err(format!(
+                "unknown reward {}, use one of {}",
+                name,
+                REWARD_NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub trait Environment {
+        fn observation_size(&self) -> usize;
+        fn reset(&mut self, seed: u64) -> Observation;
+        fn step(&mut self, action: Action) -> (Observation, f64, bool, StepInfo);
+    }
+
+    pub struct SnakeEnvironment {
+        game: Game,
+        config: GameConfig,
+        encoder: Box<dyn ObservationEncoder>,
+        reward_function: Box<dyn RewardFunction>,
+    }
+
+    fn apple_distance(game: &Game) -> u32 {
+        let head = game.get_snake_head();
+        let apple = game.get_apple();
+
+        head.0.abs_diff(apple.0) + head.1.abs_diff(apple.1)
+    }
+
+    impl SnakeEnvironment {
+        pub fn new(
+            config: GameConfig,
+            encoder: Box<dyn ObservationEncoder>,
+            reward_function: Box<dyn RewardFunction>,
+        ) -> SnakeEnvironment {
+            SnakeEnvironment {
+                game: Game::new_with(None, config, rand::random()),
+                config,
+                encoder,
+                reward_function,
+            }
+        }
+
+        pub fn get_game(&self) -> &Game {
+            &self.game
+        }
+    }
+
+    impl Default for SnakeEnvironment {
+        fn default() -> Self {
+            SnakeEnvironment::new(
+                GameConfig::training(),
+                Box::new(BinaryVisionEncoder),
+                Box::new(AppleReward::default()),
+            )
+        }
+    }
+
+    impl Environment for SnakeEnvironment {
+        fn observation_size(&self) -> usize {
+            self.encoder.size()
+        }
+
+        fn reset(&mut self, seed: u64) -> Observation {
+            self.game = Game::new_with(None, self.config, seed);
+
+            self.encoder.encode(&self.game.get_state())
+        }
+
+        fn step(&mut self, action: Action) -> (Observation, f64, bool, StepInfo) {
+            let score_before = self.game.get_score();
+            let apple_distance_before = apple_distance(&self.game);
+
+            self.game.set_direction(action);
+            self.game.tick();
+
+            let ate_apple = self.game.get_score() != score_before;
+            let terminal_state = self.game.get_terminal_state();
+            let died = terminal_state.is_some_and(|state| state.is_death());
+
+            let transition = Transition {
+                ate_apple,
+                died,
+                apple_distance_before,
+                apple_distance_after: apple_distance(&self.game),
+            };
+
+            let info = StepInfo {
+                score: self.game.get_score(),
+                turns: self.game.get_turns(),
+                terminal_state,
+            };
+
+            (
+                self.encoder.encode(&self.game.get_state()),
+                self.reward_function.reward(&transition),
+                terminal_state.is_some(),
+                info,
+            )
+        }
+    }
+
+    // the reward, whether the episode ended and the info of one game after a step of all of
+    // them, None for a game that isn't playing
+    pub type VecStep = Option<(f64, bool, StepInfo)>;
+
+    // steps many games in lockstep with their observations stacked one row per game, so a
+    // network can act for all of them at once. `episodes` games are played in total, a game
+    // that ends starts again right away while some are left and stops playing otherwise
+    pub struct VecEnvironment {
+        environments: Vec<SnakeEnvironment>,
+        observations: Array2<f64>,
+        playing: Vec<bool>,
+        // the episodes that didn't start yet
+        episodes_left: usize,
+    }
+
+    impl VecEnvironment {
+        // there has to be at least one environment and they all need observations of the same
+        // size
+        pub fn new(environments: Vec<SnakeEnvironment>, episodes: usize) -> VecEnvironment {
+            assert!(
+                !environments.is_empty(),
+                "a VecEnvironment needs at least one environment"
+            );
+            let observation_size = environments[0].observation_size();
+            assert!(
+                environments
+                    .iter()
+                    .all(|environment| environment.observation_size() == observation_size),
+                "the environments of a VecEnvironment have observations of different sizes"
+            );
+
+            let mut environment = VecEnvironment {
+                observations: Array2::zeros((environments.len(), observation_size)),
+                playing: vec![false; environments.len()],
+                environments,
+                episodes_left: episodes,
+            };
+
+            for i in 0..environment.environments.len() {
+                environment.start_episode(i);
+            }
+
+            environment
+        }
+
+        fn start_episode(&mut self, index: usize) {
+            if self.episodes_left == 0 {
+                self.playing[index] = false;
+                return;
+            }
+
+            self.episodes_left -= 1;
+            self.playing[index] = true;
+
+            let observation = self.environments[index].reset(rand::random());
+            self.observations.row_mut(index).assign(&observation);
+        }
+
+        // one row per game, the rows of games that aren't playing have no meaning
+        pub fn get_observations(&self) -> ArrayView2<'_, f64> {
+            self.observations.view()
+        }
+
+        // the direction every snake goes, for actions relative to it
+        pub fn get_directions(&self) -> Vec<Direction> {
+            self.environments
+                .iter()
+                .map(|environment| environment.get_game().get_direction())
+                .collect()
+        }
+
+        // true once every episode was played
+        pub fn is_done(&self) -> bool {
+            !self.playing.contains(&true)
+        }
+
+        // one action for every game, the games that aren't playing ignore theirs
+        pub fn step(&mut self, actions: &[Action]) -> Vec<VecStep> {
+            assert_eq!(
+                actions.len(),
+                self.environments.len(),
+                "a VecEnvironment needs one action for every game"
+            );
+
+            let mut steps = Vec::with_capacity(self.environments.len());
+
+            for (i, action) in actions.iter().enumerate() {
+                if !self.playing[i] {
+                    steps.push(None);
+                    continue;
+                }
+
+                let (observation, reward, done, info) = self.environments[i].step(*action);
+
+                if done {
+                    self.start_episode(i);
+                } else {
+                    self.observations.row_mut(i).assign(&observation);
+                }
+
+                steps.push(Some((reward, done, info)));
+            }
+
+            steps
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn create_environment(games: usize, episodes: usize) -> VecEnvironment {
+            let environments = (0..games)
+                .map(|_| {
+                    SnakeEnvironment::new(
+                        GameConfig::training(),
+                        Box::new(BinaryVisionEncoder),
+                        Box::new(AppleReward::default()),
+                    )
+                })
+                .collect();
+
+            VecEnvironment::new(environments, episodes)
+        }
+
+        #[test]
+        fn plays_every_episode_once() {
+            for (games, episodes) in [(3, 7), (4, 2), (1, 3)] {
+                let mut environment = create_environment(games, episodes);
+                let mut done_count = 0;
+
+                // going up until the wall ends every game after a few moves
+                while !environment.is_done() {
+                    let steps = environment.step(&vec![Direction::Up; games]);
+
+                    assert_eq!(steps.len(), games);
+                    done_count += steps.iter().flatten().filter(|(_, done, _)| *done).count();
+                }
+
+                assert_eq!(done_count, episodes);
+                let steps = environment.step(&vec![Direction::Up; games]);
+                assert!(steps.iter().all(|step| step.is_none()));
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "one action for every game")]
+        fn rejects_missing_actions() {
+            create_environment(3, 3).step(&[Direction::Up; 2]);
+        }
+
+        #[test]
+        #[should_panic(expected = "at least one environment")]
+        fn rejects_no_environments() {
+            create_environment(0, 3);
+        }
+    }
+}
@@ -1,19 +1,19 @@
 pub mod layer {
     use std::io::{Read, Write};
 
-    use ndarray::{Array, Array1, Array2};
+    use ndarray::linalg::general_mat_mul;
+    use ndarray::{Array, Array1, Array2, ArrayView2, ArrayViewMut2};
     use ndarray_rand::rand_distr::Uniform;
     use ndarray_rand::RandomExt;
     use rand::Rng;
 
-    fn sigmoid(x: f64) -> f64 {
-        1.0 / (1.0 + (-x).exp())
-    }
+    use crate::ai::activation::activation::ActivationFunction;
 
     #[derive(Debug)]
     pub struct Layer {
         weights: Array2<f64>,
         biases: Array1<f64>,
+        activation: ActivationFunction,
     }
 
     impl Layer {
@@ -31,52 +31,83 @@ pub mod layer {
             });
         }
 
-        pub fn new(input_size: u32, output_size: u32) -> Layer {
+        pub fn new(input_size: u32, output_size: u32, activation: ActivationFunction) -> Layer {
             let uniform = Uniform::new(-1.0, 1.0);
 
             Layer {
                 weights: Array::random((output_size as usize, input_size as usize), uniform),
                 biases: Array::random(output_size as usize, uniform),
+                activation,
             }
         }
 
         pub fn feed_forward(&self, input: &Array1<f64>) -> Array1<f64> {
             let mut output = self.weights.dot(input) + &self.biases;
-            output.mapv_inplace(|x| sigmoid(x));
+            self.activation.apply(output.view_mut());
             output
         }
 
-        pub fn write_to_file(&self, file: &mut std::fs::File) {
+        // one row of `input` per game, writes the matching row of `output` without allocating
+        pub fn feed_forward_batch(&self, input: ArrayView2<f64>, mut output: ArrayViewMut2<f64>) {
+            general_mat_mul(1.0, &input, &self.weights.t(), 0.0, &mut output);
+            output += &self.biases;
+
+            for row in output.rows_mut() {
+                self.activation.apply(row);
+            }
+        }
+
+        // one row per output, one column per input
+        pub fn get_weights(&self) -> &Array2<f64> {
+            &self.weights
+        }
+
+        pub fn get_activation(&self) -> ActivationFunction {
+            self.activation
+        }
+
+        pub fn write_to_file(&self, file: &mut impl Write) -> std::io::Result<()> {
             for row in self.weights.rows() {
                 for elem in row.iter() {
-                    file.write(&elem.to_le_bytes());
+                    file.write_all(&elem.to_le_bytes())?;
                 }
             }
 
             for elem in self.biases.iter() {
-                file.write(&elem.to_le_bytes());
+                file.write_all(&elem.to_le_bytes())?;
             }
+
+            Ok(())
         }
 
-        pub fn new_from_file(input_size: u32, output_size: u32, file: &mut std::fs::File) -> Layer {
+        pub fn new_from_file(
+            input_size: u32,
+            output_size: u32,
+            activation: ActivationFunction,
+            file: &mut impl Read,
+        ) -> std::io::Result<Layer> {
             let mut weights = Array2::<f64>::zeros((output_size as usize, input_size as usize));
             let mut biases = Array1::<f64>::zeros(output_size as usize);
 
             for mut row in weights.rows_mut() {
                 for elem in row.iter_mut() {
                     let mut buf = [0; 8];
-                    file.read_exact(&mut buf);
+                    file.read_exact(&mut buf)?;
                     *elem = f64::from_le_bytes(buf);
                 }
             }
 
             for elem in biases.iter_mut() {
                 let mut buf = [0; 8];
-                file.read(&mut buf);
+                file.read_exact(&mut buf)?;
                 *elem = f64::from_le_bytes(buf);
             }
 
-            Layer { weights, biases }
+            Ok(Layer {
+                weights,
+                biases,
+                activation,
+            })
         }
     }
 
@@ -85,6 +116,7 @@ pub mod layer {
             Layer {
                 weights: self.weights.clone(),
                 biases: self.biases.clone(),
+                activation: self.activation,
             }
         }
     }
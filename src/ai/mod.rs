@@ -1,4 +1,9 @@
+pub mod actions;
+pub mod activation;
 pub mod ai_game;
+pub mod environment;
 mod layer;
+pub mod model;
 pub mod neural_network;
+pub mod observation;
 pub mod train_netwrok;
@@ -0,0 +1,250 @@
+pub mod model {
+    use crate::ai::{
+        actions::actions::ActionSpace, activation::activation::ActivationFunction,
+        neural_network::neural_network::NeuralNetwork, observation::observation::DEFAULT_ENCODER,
+    };
+
+    // a model file is a text header followed by the weights of the network:
+    //   snake-model 1
+    //   encoder binary
+    //   actions absolute
+    //   shape 24 40 40 4
+    //   activations sigmoid sigmoid sigmoid
+    //   (empty line)
+    // a header without actions is absolute and one without activations is sigmoid in every
+    // layer. files without the header are from before it existed and only have the weights
+    // of a LEGACY_SHAPE absolute sigmoid network that uses the default encoder
+    const MAGIC: &str = "snake-model 1";
+    const LEGACY_SHAPE: [u32; 4] = [24, 40, 40, 4];
+
+    pub struct Model {
+        pub network: NeuralNetwork,
+        // the id of the observation encoder, see create_encoder
+        pub encoder: String,
+        pub action_space: ActionSpace,
+    }
+
+    struct Header {
+        encoder: String,
+        action_space: ActionSpace,
+        shape: Vec<u32>,
+        activations: Vec<ActivationFunction>,
+    }
+
+    impl Model {
+        pub fn new_from_file(path: &str) -> Result<Model, String> {
+            let bytes = std::fs::read(path)
+                .map_err(|error| format!("could not read {}: {}", path, error))?;
+
+            let (header, weights) = if bytes.starts_with(MAGIC.as_bytes()) {
+                read_header(&bytes).map_err(|error| format!("{} in {}", error, path))?
+            } else {
+                let header = Header {
+                    encoder: DEFAULT_ENCODER.to_string(),
+                    action_space: ActionSpace::Absolute,
+                    shape: LEGACY_SHAPE.to_vec(),
+                    activations: vec![ActivationFunction::Sigmoid; LEGACY_SHAPE.len() - 1],
+                };
+
+                (header, &bytes[..])
+            };
+
+            if weights.len() != NeuralNetwork::get_weights_size(&header.shape) {
+                return Err(format!(
+                    "{} doesn't have the weights of a {:?} network",
+                    path, header.shape
+                ));
+            }
+
+            let network = NeuralNetwork::new_from_weights(
+                &header.shape,
+                &header.activations,
+                &mut &weights[..],
+            )
+            .map_err(|error| format!("could not read the weights of {}: {}", path, error))?;
+
+            Ok(Model {
+                network,
+                encoder: header.encoder,
+                action_space: header.action_space,
+            })
+        }
+    }
+
+    // returns the header and the bytes after it
+    fn read_header(bytes: &[u8]) -> Result<(Header, &[u8]), String> {
+        let end = bytes
+            .windows(2)
+            .position(|window| window == b"\n\n")
+            .ok_or("missing the end of the header")?;
+        let text = std::str::from_utf8(&bytes[..end]).map_err(|_| "invalid header")?;
+
+        let mut encoder = None;
+        let mut action_space = ActionSpace::Absolute;
+        let mut shape = None;
+        let mut activations = None;
+
+        for line in text.lines().skip(1) {
+            let (name, value) = line.split_once(' ').unwrap_or((line, ""));
+
+            match name {
+                "encoder" => encoder = Some(value.to_string()),
+                "actions" => {
+                    action_space = ActionSpace::from_name(value)
+                        .ok_or(format!("unknown action space {}", value))?
+                }
+                "shape" => {
+                    let sizes: Result<Vec<u32>, _> =
+                        value.split_whitespace().map(|size| size.parse()).collect();
+                    shape = Some(sizes.map_err(|_| format!("invalid shape {}", value))?);
+                }
+                "activations" => {
+                    let functions: Option<Vec<ActivationFunction>> = value
+                        .split_whitespace()
+                        .map(ActivationFunction::from_name)
+                        .collect();
+                    activations = Some(functions.ok_or(format!("invalid activations {}", value))?);
+                }
+                _ => return Err(format!("unknown header line {}", line)),
+            }
+        }
+
+        let shape = shape.ok_or("missing the shape")?;
+
+        if shape.len() < 2 || shape.contains(&0) {
+            return Err(format!("invalid shape {:?}", shape));
+        }
+
+        let activations = activations.unwrap_or(vec![ActivationFunction::Sigmoid; shape.len() - 1]);
+
+        if activations.len() != shape.len() - 1 {
+            return Err(format!(
+                "{} activations for the {} layers of shape {:?}",
+                activations.len(),
+                shape.len() - 1,
+                shape
+            ));
+        }
+
+        let header = Header {
+            encoder: encoder.ok_or("missing the encoder")?,
+            action_space,
+            shape,
+            activations,
+        };
+
+        Ok((header, &bytes[end + 2..]))
+    }
+
+    pub fn write_model(
+        path: &str,
+        network: &NeuralNetwork,
+        encoder: &str,
+        action_space: ActionSpace,
+    ) -> std::io::Result<()> {
+        let shape: Vec<String> = network
+            .get_shape()
+            .iter()
+            .map(|size| size.to_string())
+            .collect();
+        let activations: Vec<&str> = network
+            .get_activation_functions()
+            .iter()
+            .map(|function| function.get_name())
+            .collect();
+
+        let mut bytes = format!(
+            "{}\nencoder {}\nactions {}\nshape {}\nactivations {}\n\n",
+            MAGIC,
+            encoder,
+            action_space.get_name(),
+            shape.join(" "),
+            activations.join(" ")
+        )
+        .into_bytes();
+        network.write_weights(&mut bytes)?;
+
+        std::fs::write(path, bytes)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_utils::test_utils::get_temp_path;
+
+        fn read_header_text(text: &str) -> Result<Header, String> {
+            let bytes = format!("{}\n{}\n\n", MAGIC, text).into_bytes();
+            read_header(&bytes).map(|(header, _)| header)
+        }
+
+        #[test]
+        fn header_defaults() {
+            let header = read_header_text("encoder binary\nshape 24 8 4").unwrap();
+
+            assert_eq!(header.encoder, "binary");
+            assert_eq!(header.action_space, ActionSpace::Absolute);
+            assert_eq!(header.shape, vec![24, 8, 4]);
+            assert_eq!(header.activations, vec![ActivationFunction::Sigmoid; 2]);
+        }
+
+        #[test]
+        fn header_rejects_bad_shapes() {
+            assert!(read_header_text("encoder binary\nshape 24").is_err());
+            assert!(read_header_text("encoder binary\nshape 24 0 4").is_err());
+            assert!(read_header_text("encoder binary\nshape 24 x 4").is_err());
+            assert!(read_header_text("encoder binary").is_err());
+        }
+
+        #[test]
+        fn header_rejects_wrong_activation_count() {
+            assert!(read_header_text("encoder binary\nshape 24 8 4\nactivations relu").is_err());
+            assert!(
+                read_header_text("encoder binary\nshape 24 8 4\nactivations relu relu relu")
+                    .is_err()
+            );
+            assert!(
+                read_header_text("encoder binary\nshape 24 8 4\nactivations relu nope").is_err()
+            );
+        }
+
+        #[test]
+        fn loads_legacy_files() {
+            let weights = vec![0; NeuralNetwork::get_weights_size(&LEGACY_SHAPE)];
+            let path = get_temp_path("legacy-model");
+
+            std::fs::write(&path, &weights).unwrap();
+            let model = Model::new_from_file(&path);
+            std::fs::write(&path, &weights[1..]).unwrap();
+            let short_model = Model::new_from_file(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            let model = model.unwrap();
+            assert_eq!(model.encoder, DEFAULT_ENCODER);
+            assert_eq!(model.action_space, ActionSpace::Absolute);
+            assert_eq!(model.network.get_shape(), LEGACY_SHAPE.to_vec());
+            assert_eq!(
+                model.network.get_activation_functions(),
+                vec![ActivationFunction::Sigmoid; 3]
+            );
+            assert!(short_model.is_err());
+        }
+
+        #[test]
+        fn round_trip() {
+            let activations = vec![ActivationFunction::Relu, ActivationFunction::Softmax];
+            let network = NeuralNetwork::new(vec![16, 8, 3], activations.clone());
+            let path = get_temp_path("model");
+
+            write_model(&path, &network, "directions", ActionSpace::Relative).unwrap();
+            let model = Model::new_from_file(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            let model = model.unwrap();
+            assert_eq!(model.encoder, "directions");
+            assert_eq!(model.action_space, ActionSpace::Relative);
+            assert_eq!(model.network.get_shape(), vec![16, 8, 3]);
+            assert_eq!(model.network.get_activation_functions(), activations);
+            assert_eq!(model.network.get_weights(), network.get_weights());
+        }
+    }
+}
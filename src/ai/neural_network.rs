@@ -1,5 +1,20 @@
 pub mod neural_network {
-    use crate::ai::layer::layer::Layer;
+    use ndarray::{ArrayView2, ArrayViewMut2};
+
+    use crate::ai::{activation::activation::ActivationFunction, layer::layer::Layer};
+
+    // the output of every layer for feed_forward_batch. they only grow, so once they fit the
+    // biggest batch feeding forward doesn't allocate
+    #[derive(Debug, Default)]
+    pub struct BatchBuffers {
+        outputs: Vec<Vec<f64>>,
+    }
+
+    impl BatchBuffers {
+        pub fn new() -> BatchBuffers {
+            BatchBuffers::default()
+        }
+    }
 
     #[derive(Debug)]
 
@@ -14,44 +29,137 @@ pub mod neural_network {
             }
         }
 
-        pub fn new(shape: Vec<u32>) -> NeuralNetwork {
+        // `activations` has one function for every layer after the input
+        pub fn new(shape: Vec<u32>, activations: Vec<ActivationFunction>) -> NeuralNetwork {
             let mut layers: Vec<Layer> = Vec::new();
 
             for i in 0..shape.len() - 1 {
-                layers.push(Layer::new(shape[i], shape[i + 1]));
+                layers.push(Layer::new(shape[i], shape[i + 1], activations[i]));
             }
 
             NeuralNetwork { layers }
         }
 
         pub fn feed_forward(&self, input: &ndarray::Array1<f64>) -> ndarray::Array1<f64> {
-            let mut output = input.clone();
+            let mut output = self.layers[0].feed_forward(input);
 
-            for layer in &self.layers {
+            for layer in &self.layers[1..] {
                 output = layer.feed_forward(&output);
             }
 
             output
         }
 
-        pub fn write_to_file(&self, path: &str) {
-            let mut file = std::fs::File::create(path).unwrap();
+        // feeds every row of `inputs` through the network at once, one row of outputs per input
+        pub fn feed_forward_batch<'a>(
+            &self,
+            inputs: ArrayView2<f64>,
+            buffers: &'a mut BatchBuffers,
+        ) -> ArrayView2<'a, f64> {
+            let batch_size = inputs.nrows();
+
+            buffers.outputs.resize_with(self.layers.len(), Vec::new);
+
+            for (i, layer) in self.layers.iter().enumerate() {
+                let (output_size, input_size) = layer.get_weights().dim();
+                let (done, rest) = buffers.outputs.split_at_mut(i);
+                let input = if i == 0 {
+                    inputs.view()
+                } else {
+                    ArrayView2::from_shape((batch_size, input_size), &done[i - 1]).unwrap()
+                };
+                let output = &mut rest[0];
+                output.resize(batch_size * output_size, 0.0);
+
+                layer.feed_forward_batch(
+                    input,
+                    ArrayViewMut2::from_shape((batch_size, output_size), output).unwrap(),
+                );
+            }
+
+            let output_size = self.layers[self.layers.len() - 1].get_weights().nrows();
+            let outputs = &buffers.outputs[self.layers.len() - 1];
+            ArrayView2::from_shape((batch_size, output_size), outputs).unwrap()
+        }
+
+        // the input followed by the output of every layer, the last one is the output of the
+        // network
+        pub fn get_activations(&self, input: &ndarray::Array1<f64>) -> Vec<ndarray::Array1<f64>> {
+            let mut activations = vec![input.clone()];
 
             for layer in &self.layers {
-                layer.write_to_file(&mut file);
+                activations.push(layer.feed_forward(activations.last().unwrap()));
             }
+
+            activations
+        }
+
+        pub fn get_weights(&self) -> Vec<&ndarray::Array2<f64>> {
+            self.layers
+                .iter()
+                .map(|layer| layer.get_weights())
+                .collect()
+        }
+
+        // the size of the input followed by the size of every layer
+        pub fn get_shape(&self) -> Vec<u32> {
+            let mut shape = vec![self.layers[0].get_weights().ncols() as u32];
+            shape.extend(
+                self.layers
+                    .iter()
+                    .map(|layer| layer.get_weights().nrows() as u32),
+            );
+
+            shape
+        }
+
+        // the activation function of every layer after the input
+        pub fn get_activation_functions(&self) -> Vec<ActivationFunction> {
+            self.layers
+                .iter()
+                .map(|layer| layer.get_activation())
+                .collect()
         }
 
-        pub fn new_from_file(shape: Vec<u32>, path: &str) -> NeuralNetwork {
-            let mut file = std::fs::File::open(path).unwrap();
+        // the same shape and activation functions with new random weights
+        pub fn new_random_like(&self) -> NeuralNetwork {
+            NeuralNetwork::new(self.get_shape(), self.get_activation_functions())
+        }
+
+        // the number of bytes write_weights writes for a network of `shape`
+        pub fn get_weights_size(shape: &[u32]) -> usize {
+            shape
+                .windows(2)
+                .map(|sizes| (sizes[0] as usize + 1) * sizes[1] as usize * 8)
+                .sum()
+        }
+
+        // the weights and biases of every layer as little endian f64, the shape isn't written
+        pub fn write_weights(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+            for layer in &self.layers {
+                layer.write_to_file(writer)?;
+            }
+
+            Ok(())
+        }
 
+        pub fn new_from_weights(
+            shape: &[u32],
+            activations: &[ActivationFunction],
+            reader: &mut impl std::io::Read,
+        ) -> std::io::Result<NeuralNetwork> {
             let mut layers: Vec<Layer> = Vec::new();
 
             for i in 0..shape.len() - 1 {
-                layers.push(Layer::new_from_file(shape[i], shape[i + 1], &mut file));
+                layers.push(Layer::new_from_file(
+                    shape[i],
+                    shape[i + 1],
+                    activations[i],
+                    reader,
+                )?);
             }
 
-            NeuralNetwork { layers }
+            Ok(NeuralNetwork { layers })
         }
     }
 
@@ -66,4 +174,57 @@ pub mod neural_network {
             NeuralNetwork { layers }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ndarray::Array2;
+        use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+        // every row of the batch has to give what feed_forward gives for it alone
+        fn check_batch(network: &NeuralNetwork, batch_size: usize, buffers: &mut BatchBuffers) {
+            let input_size = network.get_shape()[0] as usize;
+            let inputs = Array2::random((batch_size, input_size), Uniform::new(-1.0, 1.0));
+
+            let outputs = network
+                .feed_forward_batch(inputs.view(), buffers)
+                .to_owned();
+
+            assert_eq!(outputs.nrows(), batch_size);
+            for (input, output) in inputs.rows().into_iter().zip(outputs.rows()) {
+                let expected = network.feed_forward(&input.to_owned());
+
+                assert_eq!(output.len(), expected.len());
+                for (value, expected) in output.iter().zip(expected.iter()) {
+                    assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+                }
+            }
+        }
+
+        #[test]
+        fn batch_matches_single_inputs() {
+            let network = NeuralNetwork::new(
+                vec![6, 5, 4, 3],
+                vec![
+                    ActivationFunction::Relu,
+                    ActivationFunction::Tanh,
+                    ActivationFunction::Softmax,
+                ],
+            );
+            let mut buffers = BatchBuffers::new();
+
+            // the buffers grow, shrink and grow again
+            for batch_size in [1, 8, 3, 1, 8] {
+                check_batch(&network, batch_size, &mut buffers);
+            }
+        }
+
+        #[test]
+        fn batch_matches_every_activation() {
+            for activation in ActivationFunction::ALL {
+                let network = NeuralNetwork::new(vec![4, 6, 3], vec![activation; 2]);
+                check_batch(&network, 5, &mut BatchBuffers::new());
+            }
+        }
+    }
 }
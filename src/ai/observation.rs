@@ -0,0 +1,430 @@
+pub mod observation {
+    use crate::game::game::{Direction, GameState, MapItem};
+    use ndarray::Array1;
+
+    pub type Observation = Array1<f64>;
+
+    // encoders are shared between the copies of an agent so they can't hold state
+    pub trait ObservationEncoder: Send + Sync {
+        fn size(&self) -> usize;
+        fn encode(&self, state: &GameState) -> Observation;
+        // the name create_encoder builds the same encoder from, saved in model files
+        fn get_id(&self) -> String;
+        // the rays the encoder looks along, drawn by the sensor overlay. empty for encoders that
+        // don't look along rays
+        fn get_sensor_rays(&self, _state: &GameState) -> Vec<Ray> {
+            Vec::new()
+        }
+    }
+
+    // a shared encoder is an encoder too, so it can be given to a SnakeEnvironment
+    impl<T: ObservationEncoder + ?Sized> ObservationEncoder for std::sync::Arc<T> {
+        fn size(&self) -> usize {
+            self.as_ref().size()
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            self.as_ref().encode(state)
+        }
+
+        fn get_id(&self) -> String {
+            self.as_ref().get_id()
+        }
+
+        fn get_sensor_rays(&self, state: &GameState) -> Vec<Ray> {
+            self.as_ref().get_sensor_rays(state)
+        }
+    }
+
+    pub const DEFAULT_ENCODER: &str = "binary";
+    pub const ENCODER_NAMES: [&str; 5] = ["binary", "distance", "window:N", "grid:N", "directions"];
+
+    // an id is one or more of ENCODER_NAMES joined with +, like "binary+directions", the
+    // observations of the parts are put one after the other
+    pub fn create_encoder(id: &str) -> Result<Box<dyn ObservationEncoder>, String> {
+        let mut encoders = Vec::new();
+
+        for name in id.split('+').map(|name| name.trim()) {
+            let (name, size) = match name.split_once(':') {
+                Some((name, size)) => {
+                    let size: u32 = size
+                        .parse()
+                        .map_err(|_| format!("invalid size in encoder {}", id))?;
+                    (name, Some(size))
+                }
+                None => (name, None),
+            };
+
+            let encoder: Box<dyn ObservationEncoder> = match (name, size) {
+                ("binary", None) => Box::new(BinaryVisionEncoder),
+                ("distance", None) => Box::new(DistanceVisionEncoder),
+                ("directions", None) => Box::new(DirectionEncoder),
+                ("window", Some(size)) if size % 2 == 1 => Box::new(LocalWindowEncoder { size }),
+                ("window", _) => return Err("the window size must be odd, like window:7".into()),
+                ("grid", Some(grid_size)) if grid_size > 0 => Box::new(GridEncoder { grid_size }),
+                ("grid", _) => return Err("the grid encoder needs a size, like grid:25".into()),
+                _ => {
+                    return Err(format!(
+                        "unknown encoder {}, use one or more of {} joined with +",
+                        name,
+                        ENCODER_NAMES.join(", ")
+                    ))
+                }
+            };
+
+            encoders.push(encoder);
+        }
+
+        if encoders.len() == 1 {
+            Ok(encoders.pop().unwrap())
+        } else {
+            Ok(Box::new(CombinedEncoder { encoders }))
+        }
+    }
+
+    fn find_in_direction(
+        game_map: &[Vec<MapItem>],
+        head: (i32, i32),
+        direction: (i32, i32),
+    ) -> (u32, u32, u32) {
+        let grid_size = game_map.len() as i32;
+
+        let mut snake_distance = 0;
+        let mut food_distance = 0;
+        let mut wall_distance = 0;
+
+        let mut current = head;
+
+        loop {
+            current = (current.0 + direction.0, current.1 + direction.1);
+            wall_distance += 1;
+
+            if current.0 < 0 || current.0 >= grid_size || current.1 < 0 || current.1 >= grid_size {
+                break;
+            }
+
+            match game_map[current.1 as usize][current.0 as usize] {
+                MapItem::Apple => {
+                    food_distance = wall_distance;
+                }
+                MapItem::Snake if snake_distance == 0 => {
+                    snake_distance = wall_distance;
+                }
+                _ => {}
+            }
+        }
+
+        (snake_distance, food_distance, wall_distance)
+    }
+
+    // what the snake sees in one direction, distances are in cells and 0 when nothing was seen
+    #[derive(Debug, Clone, Copy)]
+    pub struct Ray {
+        pub direction: (i32, i32),
+        pub snake_distance: u32,
+        pub food_distance: u32,
+        pub wall_distance: u32,
+    }
+
+    // looks in 8 directions from the head, for each one: is there snake, is there an apple,
+    // and the inverse distance to the wall
+    pub struct BinaryVisionEncoder;
+
+    // the same 8 directions as BinaryVisionEncoder with the inverse distance to the first body
+    // part, to the apple and to the wall, 0 when there is no body part or apple
+    pub struct DistanceVisionEncoder;
+
+    // the size x size cells around the head, size is odd so the head is in the middle. two
+    // values for every cell: is it a wall or a body part, is it the apple
+    pub struct LocalWindowEncoder {
+        pub size: u32,
+    }
+
+    // the whole board, one-hot for every cell: empty, body, head, apple. only works for
+    // boards of grid_size
+    pub struct GridEncoder {
+        pub grid_size: u32,
+    }
+
+    // one-hot of the direction of the head and of the direction the tail is moving, in the
+    // order of Direction::ALL
+    pub struct DirectionEncoder;
+
+    pub struct CombinedEncoder {
+        encoders: Vec<Box<dyn ObservationEncoder>>,
+    }
+
+    impl BinaryVisionEncoder {
+        const DIRECTIONS: [(i32, i32); 8] = [
+            (0, 1),
+            (1, 1),
+            (1, 0),
+            (1, -1),
+            (0, -1),
+            (-1, -1),
+            (-1, 0),
+            (-1, 1),
+        ];
+
+        pub fn get_rays(state: &GameState) -> Vec<Ray> {
+            let game_map = state.get_map();
+            let head = state.get_head();
+
+            Self::DIRECTIONS
+                .iter()
+                .map(|direction| {
+                    let (snake_distance, food_distance, wall_distance) =
+                        find_in_direction(&game_map, head, *direction);
+
+                    Ray {
+                        direction: *direction,
+                        snake_distance,
+                        food_distance,
+                        wall_distance,
+                    }
+                })
+                .collect()
+        }
+    }
+
+    impl ObservationEncoder for BinaryVisionEncoder {
+        fn size(&self) -> usize {
+            Self::DIRECTIONS.len() * 3
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let mut input = Array1::zeros(self.size());
+
+            for (i, ray) in Self::get_rays(state).iter().enumerate() {
+                input[i * 3] = if ray.snake_distance == 0 {
+                    0.0
+                } else {
+                    //1.0 / snake_distance as f64
+                    1.0
+                };
+
+                input[1 + i * 3] = if ray.food_distance == 0 {
+                    0.0
+                } else {
+                    //1.0 / food_distance as f64
+                    1.0
+                };
+
+                input[2 + i * 3] = 1.0 / ray.wall_distance as f64;
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            "binary".to_string()
+        }
+
+        fn get_sensor_rays(&self, state: &GameState) -> Vec<Ray> {
+            Self::get_rays(state)
+        }
+    }
+
+    fn get_inverse_distance(distance: u32) -> f64 {
+        if distance == 0 {
+            0.0
+        } else {
+            1.0 / distance as f64
+        }
+    }
+
+    impl ObservationEncoder for DistanceVisionEncoder {
+        fn size(&self) -> usize {
+            BinaryVisionEncoder::DIRECTIONS.len() * 3
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let mut input = Array1::zeros(self.size());
+
+            for (i, ray) in BinaryVisionEncoder::get_rays(state).iter().enumerate() {
+                input[i * 3] = get_inverse_distance(ray.snake_distance);
+                input[1 + i * 3] = get_inverse_distance(ray.food_distance);
+                input[2 + i * 3] = get_inverse_distance(ray.wall_distance);
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            "distance".to_string()
+        }
+
+        fn get_sensor_rays(&self, state: &GameState) -> Vec<Ray> {
+            BinaryVisionEncoder::get_rays(state)
+        }
+    }
+
+    impl ObservationEncoder for LocalWindowEncoder {
+        fn size(&self) -> usize {
+            (self.size * self.size * 2) as usize
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let game_map = state.get_map();
+            let head = state.get_head();
+            let radius = (self.size / 2) as i32;
+
+            let mut input = Array1::zeros(self.size());
+            let mut i = 0;
+
+            for y in -radius..=radius {
+                for x in -radius..=radius {
+                    let cell = (head.0 + x, head.1 + y);
+
+                    if !state.is_inside(cell) {
+                        input[i] = 1.0;
+                    } else {
+                        match game_map[cell.1 as usize][cell.0 as usize] {
+                            // the head itself isn't an obstacle
+                            MapItem::Snake if (x, y) != (0, 0) => input[i] = 1.0,
+                            MapItem::Apple => input[i + 1] = 1.0,
+                            _ => {}
+                        }
+                    }
+
+                    i += 2;
+                }
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            format!("window:{}", self.size)
+        }
+    }
+
+    impl ObservationEncoder for GridEncoder {
+        fn size(&self) -> usize {
+            (self.grid_size * self.grid_size * 4) as usize
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let mut input = Array1::zeros(self.size());
+
+            let game_map = state.get_map();
+            let head = state.get_head();
+            // cells of a bigger board than the encoder's are left out
+            let size = (self.grid_size as usize).min(game_map.len());
+
+            for (y, row) in game_map.iter().enumerate().take(size) {
+                for (x, item) in row.iter().enumerate().take(size) {
+                    let channel = match item {
+                        _ if (x as i32, y as i32) == head => 2,
+                        MapItem::Empty => 0,
+                        MapItem::Snake => 1,
+                        MapItem::Apple => 3,
+                    };
+
+                    input[(y * self.grid_size as usize + x) * 4 + channel] = 1.0;
+                }
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            format!("grid:{}", self.grid_size)
+        }
+    }
+
+    impl ObservationEncoder for DirectionEncoder {
+        fn size(&self) -> usize {
+            Direction::ALL.len() * 2
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let mut input = Array1::zeros(self.size());
+
+            let head_direction = Direction::ALL
+                .iter()
+                .position(|direction| *direction == state.direction)
+                .unwrap();
+            input[head_direction] = 1.0;
+
+            // the tail moves to the part before it, right after eating both are on the same
+            // cell and the tail doesn't move
+            let tail = state.snake[state.snake.len() - 1];
+            let before_tail = state.snake[state.snake.len() - 2];
+            let tail_vector = (before_tail.0 - tail.0, before_tail.1 - tail.1);
+
+            if let Some(tail_direction) = Direction::ALL
+                .iter()
+                .position(|direction| direction.to_vector() == tail_vector)
+            {
+                input[Direction::ALL.len() + tail_direction] = 1.0;
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            "directions".to_string()
+        }
+    }
+
+    impl ObservationEncoder for CombinedEncoder {
+        fn size(&self) -> usize {
+            self.encoders.iter().map(|encoder| encoder.size()).sum()
+        }
+
+        fn encode(&self, state: &GameState) -> Observation {
+            let mut input = Array1::zeros(self.size());
+            let mut start = 0;
+
+            for encoder in &self.encoders {
+                let size = encoder.size();
+                input
+                    .slice_mut(ndarray::s![start..start + size])
+                    .assign(&encoder.encode(state));
+                start += size;
+            }
+
+            input
+        }
+
+        fn get_id(&self) -> String {
+            let ids: Vec<String> = self
+                .encoders
+                .iter()
+                .map(|encoder| encoder.get_id())
+                .collect();
+            ids.join("+")
+        }
+
+        // binary and distance look along the same rays, they are only drawn once
+        fn get_sensor_rays(&self, state: &GameState) -> Vec<Ray> {
+            self.encoders
+                .iter()
+                .map(|encoder| encoder.get_sensor_rays(state))
+                .find(|rays| !rays.is_empty())
+                .unwrap_or_default()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::game::game::{Game, GameConfig};
+
+        #[test]
+        fn only_ray_encoders_have_sensor_rays() {
+            let state = Game::new_with(None, GameConfig::default(), 0).get_state();
+            let get_ray_count =
+                |id: &str| create_encoder(id).unwrap().get_sensor_rays(&state).len();
+
+            assert_eq!(get_ray_count("binary"), 8);
+            assert_eq!(get_ray_count("distance"), 8);
+            assert_eq!(get_ray_count("binary+distance"), 8);
+            assert_eq!(get_ray_count("directions+binary"), 8);
+            assert_eq!(get_ray_count("window:7"), 0);
+            assert_eq!(get_ray_count("grid:25"), 0);
+            assert_eq!(get_ray_count("directions"), 0);
+        }
+    }
+}
@@ -3,97 +3,161 @@ pub mod train_network {
     const MUTATION_PERECENT: f64 = 20.;
     const AVERAGE_AMOUNT: usize = 10;
 
+    use std::sync::Arc;
+
     use piston::UpdateArgs;
 
     use crate::{
-        ai::{ai_game::ai_game::AiGame, neural_network::neural_network::NeuralNetwork},
+        agents::neural_agent::neural_agent::{parse_layout, NeuralAgent},
+        ai::{
+            actions::actions::ActionSpace,
+            ai_game::ai_game::{get_fitness, AiGame},
+            environment::environment::{AppleReward, SnakeEnvironment, VecEnvironment},
+            neural_network::neural_network::BatchBuffers,
+            observation::observation::{create_encoder, ObservationEncoder},
+        },
         constants::constants::TIME_BETWEEN_MOVES,
+        game::game::GameConfig,
     };
 
     pub fn run_game(ai_game: &mut AiGame) {
-        let mut last_score = ai_game.get_game().get_score();
-        let mut turns_from_last_score = 0;
-
-        while ai_game.get_game().is_alive()
-            && turns_from_last_score < 150
-            && ai_game.get_game().get_turns() < 2500
-        {
+        while !ai_game.get_game().is_over() {
             let update_args: UpdateArgs = UpdateArgs {
                 dt: TIME_BETWEEN_MOVES,
             };
             ai_game.update(&update_args);
-
-            if last_score != ai_game.get_game().get_score() {
-                last_score = ai_game.get_game().get_score();
-                turns_from_last_score = 0;
-            } else {
-                turns_from_last_score += 1;
-            }
         }
     }
 
-    pub fn get_average_fitness(ai_game: &AiGame, amount: usize) -> f64 {
+    // plays AVERAGE_AMOUNT games at once in lockstep so every move of all of them is a single
+    // pass through the network
+    pub fn get_average_fitness(agent: &NeuralAgent, buffers: &mut BatchBuffers) -> f64 {
+        let environments = (0..AVERAGE_AMOUNT)
+            .map(|_| {
+                SnakeEnvironment::new(
+                    GameConfig::training(),
+                    agent.get_observation_encoder(),
+                    Box::new(AppleReward::default()),
+                )
+            })
+            .collect();
+        let mut environment = VecEnvironment::new(environments, AVERAGE_AMOUNT);
+
         let mut total_fitness = 0.;
 
-        for _ in 0..amount {
-            let mut game = ai_game.clone();
-            run_game(&mut game);
-            total_fitness += game.calc_fitness();
+        while !environment.is_done() {
+            let actions = agent.act_batch(
+                environment.get_observations(),
+                &environment.get_directions(),
+                buffers,
+            );
+
+            for (_, done, info) in environment.step(&actions).into_iter().flatten() {
+                if done {
+                    total_fitness += get_fitness(info.score, info.turns);
+                }
+            }
         }
 
-        total_fitness / amount as f64
+        total_fitness / AVERAGE_AMOUNT as f64
+    }
+
+    // the fitness is saved next to the network so it can be shown when the model is watched
+    fn write_fitness(network_path: &str, fitness: f64) {
+        std::fs::write(format!("{}.fitness", network_path), fitness.to_string()).unwrap();
     }
 
-    pub fn train_network(save_folder: &str, upload_file: &str) {
+    pub fn read_fitness(network_path: &str) -> Option<f64> {
+        std::fs::read_to_string(format!("{}.fitness", network_path))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    // `encoder` is the id of the observation encoder of new networks, `action_space` what
+    // their outputs mean and `layout` their layers (see parse_layout), a loaded network keeps
+    // the ones it was trained with. only returns if the loaded file, the encoder or the
+    // layout is invalid
+    pub fn train_network(
+        save_folder: &str,
+        upload_file: &str,
+        encoder: &str,
+        action_space: ActionSpace,
+        layout: &str,
+    ) -> Result<(), String> {
         let mut population: Vec<AiGame> = Vec::new();
 
-        if upload_file != "" {
-            // todo: move shape to file
-            let base_nn = NeuralNetwork::new_from_file(vec![24, 40, 40, 4], upload_file);
+        // the random networks of every generation are like the first one
+        let base_agent = if !upload_file.is_empty() {
+            let base_agent = NeuralAgent::new_from_file(upload_file)?;
 
             for _ in 0..POPULATION_SIZE {
-                population.push(AiGame::new_from(None, &base_nn));
+                population.push(AiGame::new_from(None, GameConfig::training(), &base_agent));
             }
+
+            base_agent
         } else {
+            let encoder: Arc<dyn ObservationEncoder> = Arc::from(create_encoder(encoder)?);
+            let base_agent = NeuralAgent::new_random(encoder, action_space, &parse_layout(layout)?);
+
             for _ in 0..POPULATION_SIZE {
-                population.push(AiGame::new(None));
+                population.push(AiGame::new_from(
+                    None,
+                    GameConfig::training(),
+                    &base_agent.new_random_like(),
+                ));
             }
-        }
+
+            base_agent
+        };
 
         let mut gen = 0;
 
         let mut best_fintess = 0.;
         let mut best_of_all = population[0].clone();
 
+        let mut buffers = BatchBuffers::new();
+
         loop {
-            for game in &mut population {
-                run_game(game);
-            }
+            // every network is ranked by its average over AVERAGE_AMOUNT games
+            let mut ranked: Vec<(f64, AiGame)> = population
+                .into_iter()
+                .map(|game| (get_average_fitness(game.get_agent(), &mut buffers), game))
+                .collect();
+            ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-            population.sort_by(|a, b| b.calc_fitness().total_cmp(&a.calc_fitness()));
+            let average_fitness = ranked[0].0;
+            population = ranked.into_iter().map(|(_, game)| game).collect();
 
-            let average_fitness = get_average_fitness(&population[0], AVERAGE_AMOUNT);
+            // one more game of the best network for its replay
+            run_game(&mut population[0]);
 
             if best_fintess < average_fitness {
                 best_fintess = average_fitness;
                 best_of_all = population[0].clone();
 
-                best_of_all
-                    .get_neural_network()
-                    .write_to_file(&format!("{}/best.bin", save_folder));
+                let best_path = format!("{}/best.bin", save_folder);
+                best_of_all.get_agent().write_to_file(&best_path).unwrap();
+                write_fitness(&best_path, average_fitness);
             }
 
+            let gen_path = format!("{}/best_of_gen_{}.bin", save_folder, gen);
+            population[0].get_agent().write_to_file(&gen_path).unwrap();
+            write_fitness(&gen_path, average_fitness);
+
             population[0]
-                .get_neural_network()
-                .write_to_file(&format!("{}/best_of_gen_{}.bin", save_folder, gen));
+                .get_game()
+                .get_replay()
+                .write_to_file(&format!("{}/best_of_gen_{}.replay", save_folder, gen))
+                .unwrap();
 
             println!(
-                " gen {} best fintess {} with score {} in {} turns and average {}",
+                " gen {} best average fintess {}, its replay game scored {} in {} turns",
                 gen,
-                population[0].calc_fitness(),
+                average_fitness,
                 population[0].get_game().get_score(),
-                population[0].get_game().get_turns(),
-                average_fitness
+                population[0].get_game().get_turns()
             );
 
             // create the next generation
@@ -103,11 +167,19 @@ pub mod train_network {
             new_population.push(best_of_all.clone());
 
             for _ in 2..10 {
-                new_population.push(AiGame::new(None));
+                new_population.push(AiGame::new_from(
+                    None,
+                    GameConfig::training(),
+                    &base_agent.new_random_like(),
+                ));
             }
 
             for _ in 10..POPULATION_SIZE {
-                new_population.push(AiGame::new_from(None, &population[0].get_neural_network()));
+                new_population.push(AiGame::new_from(
+                    None,
+                    GameConfig::training(),
+                    population[0].get_agent(),
+                ));
                 let last_element = new_population.len() - 1;
                 new_population[last_element].mutate(MUTATION_PERECENT);
             }
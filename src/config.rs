@@ -0,0 +1,52 @@
+pub mod config {
+    // `name = value` lines, lines starting with # are comments (values can have a #, like
+    // colors)
+    #[derive(Default)]
+    pub struct Config {
+        values: Vec<(String, String)>,
+    }
+
+    impl Config {
+        // a missing file is an empty config so every setting keeps its default
+        pub fn new_from_file(path: &str) -> Result<Config, String> {
+            let text = match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(error) => return Err(format!("could not read {}: {}", path, error)),
+            };
+
+            let mut values = Vec::new();
+
+            for (i, line) in text.lines().enumerate() {
+                let line = line.trim();
+
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+
+                let (name, value) = line.split_once('=').ok_or(format!(
+                    "{} line {}: expected name = value",
+                    path,
+                    i + 1
+                ))?;
+
+                values.push((name.trim().to_string(), value.trim().to_string()));
+            }
+
+            Ok(Config { values })
+        }
+
+        // when a name is set more than once the last value is used
+        pub fn get(&self, name: &str) -> Option<&str> {
+            self.values
+                .iter()
+                .rev()
+                .find(|(value_name, _)| value_name == name)
+                .map(|(_, value)| value.as_str())
+        }
+
+        pub fn get_names(&self) -> Vec<&str> {
+            self.values.iter().map(|(name, _)| name.as_str()).collect()
+        }
+    }
+}
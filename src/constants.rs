@@ -2,5 +2,20 @@ pub mod constants {
     pub const TIME_BETWEEN_MOVES: f64 = 0.20f64;
     pub const WINDOW_SIZE: u32 = 1000;
     pub const GRID_SIZE: u32 = 25;
-    pub const SNAKE_SIZE: f64 = WINDOW_SIZE as f64 / GRID_SIZE as f64;
+    // the starting snake is on the 3rd and 4th column, so smaller boards can't hold it
+    pub const MIN_GRID_SIZE: u32 = 4;
+    // one cell per pixel of the window
+    pub const MAX_GRID_SIZE: u32 = WINDOW_SIZE;
+    pub const MAX_TURNS: u32 = 2500;
+    pub const MAX_TURNS_WITHOUT_APPLE: u32 = 150;
+    pub const MAX_QUEUED_INPUTS: usize = 3;
+    pub const HIGH_SCORES_FILE: &str = "high_scores.txt";
+    pub const CONFIG_FILE: &str = "snake.cfg";
+    // multipliers of the game speed
+    pub const MIN_SPEED: f64 = 0.125;
+    pub const MAX_SPEED: f64 = 64.0;
+    // seconds of moves an ai game runs per update in turbo mode
+    pub const TURBO_TIME_PER_UPDATE: f64 = 0.005;
+    // width of the network panel next to the board when an ai game shows it
+    pub const NETWORK_PANEL_WIDTH: f64 = 400.0;
 }
@@ -0,0 +1,224 @@
+pub mod evaluation {
+    use crate::{
+        agents::agent::agent::Agent,
+        ai::{
+            environment::environment::{
+                AppleReward, Environment, RewardFunction, SnakeEnvironment,
+            },
+            observation::observation::BinaryVisionEncoder,
+        },
+        game::game::{GameConfig, TerminalState},
+        replay::replay::Replay,
+    };
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct GameResult {
+        pub score: u32,
+        pub length: u32,
+        pub turns: u32,
+        pub terminal_state: TerminalState,
+        // the sum of the rewards of every move
+        pub reward: f64,
+    }
+
+    fn play_game(
+        agent: &mut dyn Agent,
+        environment: &mut SnakeEnvironment,
+        seed: u64,
+    ) -> GameResult {
+        environment.reset(seed);
+
+        let mut reward = 0.0;
+
+        loop {
+            let action = agent.act(&environment.get_game().get_state());
+            let (_, step_reward, done, info) = environment.step(action);
+            reward += step_reward;
+
+            if done {
+                return GameResult {
+                    score: info.score,
+                    length: info.score + 2,
+                    turns: info.turns,
+                    terminal_state: info.terminal_state.unwrap(),
+                    reward,
+                };
+            }
+        }
+    }
+
+    pub fn record_game(agent: &mut dyn Agent, config: GameConfig, seed: u64) -> Replay {
+        let mut environment = SnakeEnvironment::new(
+            config,
+            Box::new(BinaryVisionEncoder),
+            Box::new(AppleReward::default()),
+        );
+        play_game(agent, &mut environment, seed);
+
+        environment.get_game().get_replay()
+    }
+
+    // plays `games` games, game i is played with seed `seed + i` (wrapping around) so runs can be
+    // compared.
+    // the rewards of the moves are added up with `reward_function`
+    pub fn evaluate(
+        agent: &mut dyn Agent,
+        config: GameConfig,
+        games: usize,
+        seed: u64,
+        reward_function: Box<dyn RewardFunction>,
+    ) -> Vec<GameResult> {
+        let mut environment =
+            SnakeEnvironment::new(config, Box::new(BinaryVisionEncoder), reward_function);
+
+        (0..games)
+            .map(|i| play_game(agent, &mut environment, seed.wrapping_add(i as u64)))
+            .collect()
+    }
+
+    pub struct EvaluationReport {
+        pub games: usize,
+        pub mean_score: f64,
+        pub median_score: f64,
+        pub mean_reward: f64,
+        pub percentiles: Vec<(u32, u32)>,
+        pub max_score: u32,
+        pub average_length: f64,
+        pub steps_per_apple: Option<f64>,
+        pub terminal_states: Vec<(TerminalState, usize)>,
+    }
+
+    const PERCENTILES: [u32; 4] = [10, 25, 75, 90];
+
+    fn get_json_name(terminal_state: TerminalState) -> &'static str {
+        match terminal_state {
+            TerminalState::HitWall => "hit_wall",
+            TerminalState::HitSelf => "hit_self",
+            TerminalState::Starved => "starved",
+            TerminalState::TurnLimit => "turn_limit",
+            TerminalState::BoardFilled => "board_filled",
+        }
+    }
+
+    impl EvaluationReport {
+        pub fn new(results: &[GameResult]) -> EvaluationReport {
+            let games = results.len();
+
+            let mut scores: Vec<u32> = results.iter().map(|result| result.score).collect();
+            scores.sort();
+
+            // nearest rank percentile
+            let percentile = |percent: u32| -> u32 {
+                let rank = (percent as f64 / 100.0 * games as f64).ceil() as usize;
+                scores[rank.clamp(1, games) - 1]
+            };
+
+            let median_score = if games.is_multiple_of(2) {
+                (scores[games / 2 - 1] + scores[games / 2]) as f64 / 2.0
+            } else {
+                scores[games / 2] as f64
+            };
+
+            let total_score: u32 = scores.iter().sum();
+            let total_turns: u32 = results.iter().map(|result| result.turns).sum();
+            let total_length: u32 = results.iter().map(|result| result.length).sum();
+            let total_reward: f64 = results.iter().map(|result| result.reward).sum();
+
+            let terminal_states = TerminalState::ALL
+                .into_iter()
+                .map(|terminal_state| {
+                    let count = results
+                        .iter()
+                        .filter(|result| result.terminal_state == terminal_state)
+                        .count();
+                    (terminal_state, count)
+                })
+                .collect();
+
+            EvaluationReport {
+                games,
+                mean_score: total_score as f64 / games as f64,
+                median_score,
+                mean_reward: total_reward / games as f64,
+                percentiles: PERCENTILES
+                    .iter()
+                    .map(|percent| (*percent, percentile(*percent)))
+                    .collect(),
+                max_score: scores[games - 1],
+                average_length: total_length as f64 / games as f64,
+                steps_per_apple: if total_score == 0 {
+                    None
+                } else {
+                    Some(total_turns as f64 / total_score as f64)
+                },
+                terminal_states,
+            }
+        }
+
+        pub fn to_text(&self) -> String {
+            let mut text = format!("games: {}\n", self.games);
+            text += &format!("mean score: {:.2}\n", self.mean_score);
+            text += &format!("median score: {}\n", self.median_score);
+
+            for (percent, score) in &self.percentiles {
+                text += &format!("p{} score: {}\n", percent, score);
+            }
+
+            text += &format!("max score: {}\n", self.max_score);
+            text += &format!("average length: {:.2}\n", self.average_length);
+            text += &format!("mean reward: {:.2}\n", self.mean_reward);
+
+            match self.steps_per_apple {
+                Some(steps) => text += &format!("steps per apple: {:.2}\n", steps),
+                None => text += "steps per apple: -\n",
+            }
+
+            text += "game endings:\n";
+
+            for (terminal_state, count) in &self.terminal_states {
+                text += &format!(
+                    "  {}: {} ({:.1}%)\n",
+                    terminal_state.get_name(),
+                    count,
+                    *count as f64 * 100.0 / self.games as f64
+                );
+            }
+
+            text
+        }
+
+        pub fn to_json(&self) -> String {
+            let percentiles: Vec<String> = self
+                .percentiles
+                .iter()
+                .map(|(percent, score)| format!("\"p{}\": {}", percent, score))
+                .collect();
+
+            let terminal_states: Vec<String> = self
+                .terminal_states
+                .iter()
+                .map(|(terminal_state, count)| {
+                    format!("\"{}\": {}", get_json_name(*terminal_state), count)
+                })
+                .collect();
+
+            let steps_per_apple = match self.steps_per_apple {
+                Some(steps) => steps.to_string(),
+                None => "null".to_string(),
+            };
+
+            format!(
+                "{{\"games\": {}, \"score\": {{\"mean\": {}, \"median\": {}, {}, \"max\": {}}}, \"average_length\": {}, \"mean_reward\": {}, \"steps_per_apple\": {}, \"endings\": {{{}}}}}",
+                self.games,
+                self.mean_score,
+                self.median_score,
+                percentiles.join(", "),
+                self.max_score,
+                self.average_length,
+                self.mean_reward,
+                steps_per_apple,
+                terminal_states.join(", ")
+            )
+        }
+    }
+}
@@ -0,0 +1,140 @@
+pub mod export {
+    use std::io::{BufWriter, Error, ErrorKind};
+
+    use crate::{game::game::GameState, replay::replay::Replay, theme::theme::Theme};
+
+    // indices into the palette made by get_palette
+    const BACKGROUND: u8 = 0;
+    const APPLE: u8 = 1;
+    const HEAD: u8 = 2;
+    const BODY: u8 = 3;
+
+    // gif delays are in hundredths of a second
+    const LAST_FRAME_DELAY: u16 = 200;
+
+    fn to_io_error(error: impl std::error::Error) -> Error {
+        Error::other(error.to_string())
+    }
+
+    // the colors of the theme as rgb bytes, the body is body_start without the fade of the
+    // window and a color that isn't drawn is the background
+    fn get_palette(theme: &Theme) -> Vec<u8> {
+        [theme.background, theme.apple, theme.head, theme.body_start]
+            .into_iter()
+            .flat_map(|color| {
+                let color = if color[3] == 0.0 {
+                    theme.background
+                } else {
+                    color
+                };
+                [color[0], color[1], color[2]].map(|value| (value * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    // draws the state into a buffer of palette indices, `cell_size` pixels per cell.
+    // like the window the apple is drawn first and the snake on top of it
+    pub fn rasterize(state: &GameState, cell_size: u32) -> Vec<u8> {
+        let cell_size = cell_size as usize;
+        let width = state.grid_size as usize * cell_size;
+
+        let mut pixels = vec![BACKGROUND; width * width];
+
+        let mut fill_cell = |(x, y): (i32, i32), color: u8| {
+            let (x, y) = (x as usize * cell_size, y as usize * cell_size);
+
+            for row in y..y + cell_size {
+                pixels[row * width + x..row * width + x + cell_size].fill(color);
+            }
+        };
+
+        fill_cell(state.apple, APPLE);
+
+        for (i, cell) in state.snake.iter().enumerate() {
+            fill_cell(*cell, if i == 0 { HEAD } else { BODY });
+        }
+
+        pixels
+    }
+
+    // calls `write_frame` with the index and pixels of every state of the replay, from the
+    // first one to the one after the last tick. one frame is kept at a time so long games
+    // don't fill the memory
+    fn write_frames(
+        replay: &Replay,
+        cell_size: u32,
+        mut write_frame: impl FnMut(usize, &[u8]) -> std::io::Result<()>,
+    ) -> std::io::Result<()> {
+        let mut game = replay.create_game(None, 0);
+        write_frame(0, &rasterize(&game.get_state(), cell_size))?;
+
+        for (i, action) in replay.actions.iter().enumerate() {
+            game.set_direction(*action);
+            game.tick();
+            write_frame(i + 1, &rasterize(&game.get_state(), cell_size))?;
+        }
+
+        Ok(())
+    }
+
+    // `delay` is the time between ticks in hundredths of a second, the last frame is held
+    // a bit longer so the end of the game is visible before the animation loops
+    pub fn export_gif(
+        replay: &Replay,
+        path: &str,
+        cell_size: u32,
+        delay: u16,
+        theme: &Theme,
+    ) -> std::io::Result<()> {
+        let size = replay.config.grid_size * cell_size;
+
+        if size > u16::MAX as u32 {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "image is too big for a gif",
+            ));
+        }
+
+        let file = BufWriter::new(std::fs::File::create(path)?);
+        let palette = get_palette(theme);
+        let mut encoder =
+            gif::Encoder::new(file, size as u16, size as u16, &palette).map_err(to_io_error)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(to_io_error)?;
+
+        let last = replay.get_tick_count();
+
+        write_frames(replay, cell_size, |i, pixels| {
+            let mut frame = gif::Frame::from_indexed_pixels(size as u16, size as u16, pixels, None);
+            frame.delay = if i == last { LAST_FRAME_DELAY } else { delay };
+            encoder.write_frame(&frame).map_err(to_io_error)
+        })
+    }
+
+    // writes frame_00000.png, frame_00001.png, ... into `folder`, one per tick
+    pub fn export_frames(
+        replay: &Replay,
+        folder: &str,
+        cell_size: u32,
+        theme: &Theme,
+    ) -> std::io::Result<()> {
+        let size = replay.config.grid_size * cell_size;
+        let palette = get_palette(theme);
+
+        std::fs::create_dir_all(folder)?;
+
+        write_frames(replay, cell_size, |i, pixels| {
+            let path = std::path::Path::new(folder).join(format!("frame_{:05}.png", i));
+            let file = BufWriter::new(std::fs::File::create(path)?);
+
+            let mut encoder = png::Encoder::new(file, size, size);
+            encoder.set_color(png::ColorType::Indexed);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_palette(palette.clone());
+
+            let mut writer = encoder.write_header().map_err(to_io_error)?;
+            writer.write_image_data(pixels).map_err(to_io_error)
+        })
+    }
+}
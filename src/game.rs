@@ -1,10 +1,19 @@
 pub mod game {
     use crate::{
-        constants::constants::{GRID_SIZE, SNAKE_SIZE, TIME_BETWEEN_MOVES},
+        constants::constants::{GRID_SIZE, MIN_GRID_SIZE, TIME_BETWEEN_MOVES},
+        constants::constants::{
+            MAX_QUEUED_INPUTS, MAX_SPEED, MAX_TURNS, MAX_TURNS_WITHOUT_APPLE, MIN_SPEED,
+        },
+        key_bindings::key_bindings::Command,
+        replay::replay::Replay,
         snake::snake::Snake,
+        theme::theme::Theme,
     };
     use opengl_graphics::GlGraphics;
-    use piston::{Key, RenderArgs, UpdateArgs};
+    use piston::{RenderArgs, UpdateArgs};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::VecDeque;
 
     pub enum MapItem {
         Empty,
@@ -22,125 +31,524 @@ pub mod game {
         }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Direction {
+        Up,
+        Right,
+        Down,
+        Left,
+    }
+
+    impl Direction {
+        // same order as the outputs of the neural network
+        pub const ALL: [Direction; 4] = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+
+        pub fn to_vector(self) -> (i32, i32) {
+            match self {
+                Direction::Up => (0, -1),
+                Direction::Right => (1, 0),
+                Direction::Down => (0, 1),
+                Direction::Left => (-1, 0),
+            }
+        }
+
+        pub fn from_vector(vector: (i32, i32)) -> Option<Direction> {
+            Direction::ALL
+                .into_iter()
+                .find(|direction| direction.to_vector() == vector)
+        }
+
+        pub fn opposite(self) -> Direction {
+            match self {
+                Direction::Up => Direction::Down,
+                Direction::Right => Direction::Left,
+                Direction::Down => Direction::Up,
+                Direction::Left => Direction::Right,
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum TerminalState {
+        HitWall,
+        HitSelf,
+        Starved,
+        TurnLimit,
+        BoardFilled,
+    }
+
+    impl TerminalState {
+        pub const ALL: [TerminalState; 5] = [
+            TerminalState::HitWall,
+            TerminalState::HitSelf,
+            TerminalState::Starved,
+            TerminalState::TurnLimit,
+            TerminalState::BoardFilled,
+        ];
+
+        pub fn is_death(self) -> bool {
+            self == TerminalState::HitWall || self == TerminalState::HitSelf
+        }
+
+        pub fn get_name(self) -> &'static str {
+            match self {
+                TerminalState::HitWall => "hit wall",
+                TerminalState::HitSelf => "hit self",
+                TerminalState::Starved => "starved",
+                TerminalState::TurnLimit => "turn limit",
+                TerminalState::BoardFilled => "board filled",
+            }
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct GameConfig {
+        pub grid_size: u32,
+        // the game ends when no apple was eaten for this many turns
+        pub max_turns_without_apple: Option<u32>,
+        pub max_turns: Option<u32>,
+    }
+
+    impl GameConfig {
+        // the limits used while training so a looping network can't run forever
+        pub fn training() -> GameConfig {
+            GameConfig {
+                max_turns_without_apple: Some(MAX_TURNS_WITHOUT_APPLE),
+                max_turns: Some(MAX_TURNS),
+                ..GameConfig::default()
+            }
+        }
+    }
+
+    impl Default for GameConfig {
+        fn default() -> Self {
+            GameConfig {
+                grid_size: GRID_SIZE,
+                max_turns_without_apple: None,
+                max_turns: None,
+            }
+        }
+    }
+
+    // a snapshot of everything an agent is allowed to know about the game
+    #[derive(Debug, Clone)]
+    pub struct GameState {
+        pub grid_size: u32,
+        // head first
+        pub snake: Vec<(i32, i32)>,
+        pub direction: Direction,
+        pub apple: (i32, i32),
+    }
+
+    impl GameState {
+        pub fn get_head(&self) -> (i32, i32) {
+            self.snake[0]
+        }
+
+        pub fn is_inside(&self, cell: (i32, i32)) -> bool {
+            let grid_size = self.grid_size as i32;
+            cell.0 >= 0 && cell.0 < grid_size && cell.1 >= 0 && cell.1 < grid_size
+        }
+
+        // true if moving the head into the cell on the next turn kills the snake,
+        // the tail is not counted because it moves away on the same turn
+        pub fn is_deadly(&self, cell: (i32, i32)) -> bool {
+            !self.is_inside(cell) || self.snake[..self.snake.len() - 1].contains(&cell)
+        }
+
+        pub fn get_map(&self) -> Vec<Vec<MapItem>> {
+            let grid_size = self.grid_size as usize;
+            let mut map = vec![vec![MapItem::Empty; grid_size]; grid_size];
+
+            for (x, y) in self.snake.iter() {
+                map[*y as usize][*x as usize] = MapItem::Snake;
+            }
+
+            map[self.apple.1 as usize][self.apple.0 as usize] = MapItem::Apple;
+
+            map
+        }
+    }
+
+    // the board is the biggest square that fits in the window left of a panel of
+    // `panel_width`, centered so a window that isn't square gets bars on the sides. returns the
+    // top left corner and the cell size
+    pub fn get_board_layout(
+        args: &RenderArgs,
+        grid_size: u32,
+        panel_width: f64,
+    ) -> ([f64; 2], f64) {
+        let [width, height] = args.window_size;
+        let width = (width - panel_width).max(0.0);
+        let board_size = width.min(height);
+
+        (
+            [(width - board_size) / 2.0, (height - board_size) / 2.0],
+            board_size / grid_size as f64,
+        )
+    }
+
     pub struct Game {
         pub gl: Option<GlGraphics>,
+        config: GameConfig,
         snake: Snake,
         apple: (i32, i32),
         time_to_move: f64,
         new_direction: (i32, i32),
+        // key presses that didn't apply yet, one is used per tick
+        input_queue: VecDeque<Direction>,
         turns: u32,
+        turns_since_apple: u32,
+        terminal_state: Option<TerminalState>,
+        paused: bool,
+        // multiplier of the time between moves, only used by update
+        speed: f64,
+        // room kept free on the right of the window when rendering
+        panel_width: f64,
+        seed: u64,
+        // StdRng can change between rand versions, replays need the same apples every time
+        rng: ChaCha8Rng,
+        // the direction requested on every tick, enough to replay the game from its seed
+        actions: Vec<Direction>,
     }
 
     impl Game {
         pub fn new(gl: Option<GlGraphics>) -> Game {
-            Game {
+            Game::new_with(gl, GameConfig::default(), rand::random())
+        }
+
+        // the grid size has to be at least MIN_GRID_SIZE
+        pub fn new_with(gl: Option<GlGraphics>, config: GameConfig, seed: u64) -> Game {
+            assert!(
+                config.grid_size >= MIN_GRID_SIZE,
+                "the board must be at least {}x{}",
+                MIN_GRID_SIZE,
+                MIN_GRID_SIZE
+            );
+
+            let mut game = Game {
                 gl,
-                snake: Snake::new(),
-                apple: (8, (GRID_SIZE / 2) as i32),
+                config,
+                snake: Snake::new(config.grid_size),
+                apple: (0, 0),
                 time_to_move: TIME_BETWEEN_MOVES,
                 new_direction: (1, 0),
+                input_queue: VecDeque::new(),
                 turns: 0,
-            }
+                turns_since_apple: 0,
+                terminal_state: None,
+                paused: false,
+                speed: 1.0,
+                panel_width: 0.0,
+                seed,
+                rng: ChaCha8Rng::seed_from_u64(seed),
+                actions: Vec::new(),
+            };
+            game.chnage_apple_position();
+
+            game
         }
 
-        pub fn render(&mut self, args: &RenderArgs) {
+        // `progress` is how far the snake has moved towards its current cells, 1 draws it
+        // exactly on them
+        pub fn render(&mut self, args: &RenderArgs, theme: &Theme, progress: f64) {
             use graphics::*;
 
-            const BACKROUND: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
-            const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+            let grid_size = self.config.grid_size;
+            let (origin, cell_size) = self.get_board_layout(args);
+            let board_size = cell_size * grid_size as f64;
 
-            let square = rectangle::square(0.0, 0.0, SNAKE_SIZE);
+            // the apple is a circle a bit smaller than its cell
+            let apple_margin = cell_size * 0.1;
+            let apple = rectangle::square(
+                origin[0] + self.apple.0 as f64 * cell_size + apple_margin,
+                origin[1] + self.apple.1 as f64 * cell_size + apple_margin,
+                cell_size - apple_margin * 2.0,
+            );
 
             self.gl
                 .as_mut()
                 .unwrap()
                 .draw(args.viewport(), |context, gl| {
-                    clear(BACKROUND, gl);
+                    clear(theme.letterbox, gl);
 
-                    let transform = context.transform.trans(
-                        self.apple.0 as f64 * SNAKE_SIZE,
-                        self.apple.1 as f64 * SNAKE_SIZE,
+                    rectangle(
+                        theme.background,
+                        [origin[0], origin[1], board_size, board_size],
+                        context.transform,
+                        gl,
                     );
 
-                    rectangle(RED, square, transform, gl);
+                    if theme.grid[3] > 0.0 {
+                        for i in 0..=grid_size {
+                            let offset = i as f64 * cell_size;
+                            let [x, y] = origin;
+
+                            line_from_to(
+                                theme.grid,
+                                0.5,
+                                [x + offset, y],
+                                [x + offset, y + board_size],
+                                context.transform,
+                                gl,
+                            );
+                            line_from_to(
+                                theme.grid,
+                                0.5,
+                                [x, y + offset],
+                                [x + board_size, y + offset],
+                                context.transform,
+                                gl,
+                            );
+                        }
+                    }
+
+                    ellipse(theme.apple, apple, context.transform, gl);
                 });
 
-            self.snake.render(args, &mut self.gl.as_mut().unwrap())
+            self.snake.render(
+                args,
+                self.gl.as_mut().unwrap(),
+                origin,
+                cell_size,
+                theme,
+                progress,
+            )
+        }
+
+        // quitting and fullscreen are left to the front-end, turbo, sensors and network are only
+        // for ai games
+        pub fn handle_command(&mut self, command: Command) {
+            match command {
+                Command::Up => self.queue_direction(Direction::Up),
+                Command::Down => self.queue_direction(Direction::Down),
+                Command::Left => self.queue_direction(Direction::Left),
+                Command::Right => self.queue_direction(Direction::Right),
+                Command::Pause => self.toggle_pause(),
+                Command::Restart => self.restart(),
+                Command::SpeedUp => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+                Command::SpeedDown => self.speed = (self.speed / 2.0).max(MIN_SPEED),
+                Command::Turbo
+                | Command::Sensors
+                | Command::Network
+                | Command::Fullscreen
+                | Command::Quit => {}
+            }
         }
 
-        pub fn handle_input(&mut self, key: Key) {
-            self.new_direction = match key {
-                Key::Up => (0, -1),
-                Key::Down => (0, 1),
-                Key::Left => (-1, 0),
-                Key::Right => (1, 0),
-                _ => return,
+        // presses of the same direction twice in a row are ignored and once the queue is full
+        // new presses are dropped
+        pub fn queue_direction(&mut self, direction: Direction) {
+            let last = match self.input_queue.back() {
+                Some(last) => *last,
+                None => Direction::from_vector(self.snake.get_direction()).unwrap(),
             };
+
+            if direction != last && self.input_queue.len() < MAX_QUEUED_INPUTS {
+                self.input_queue.push_back(direction);
+            }
+        }
+
+        pub fn toggle_pause(&mut self) {
+            self.paused = !self.paused && !self.is_over();
         }
 
+        // starts a new game with the same config, keeping the graphics, the speed and the panel
+        pub fn restart(&mut self) {
+            let gl = self.gl.take();
+            let (speed, panel_width) = (self.speed, self.panel_width);
+            *self = Game::new_with(gl, self.config, rand::random());
+            self.speed = speed;
+            self.panel_width = panel_width;
+        }
+
+        pub fn set_direction(&mut self, direction: Direction) {
+            self.new_direction = direction.to_vector();
+        }
+
+        // picks a random free cell, the caller makes sure there is at least one
         fn chnage_apple_position(&mut self) {
-            let snake = self.snake.get_snake();
+            let grid_size = self.config.grid_size as i32;
 
-            self.apple = (
-                rand::random::<i32>().abs() % GRID_SIZE as i32,
-                rand::random::<i32>().abs() % GRID_SIZE as i32,
-            );
+            let mut occupied = vec![false; (grid_size * grid_size) as usize];
 
-            while snake.contains(&self.apple) {
-                self.apple = (
-                    rand::random::<i32>().abs() % GRID_SIZE as i32,
-                    rand::random::<i32>().abs() % GRID_SIZE as i32,
-                );
+            for (x, y) in self.snake.get_snake() {
+                occupied[(y * grid_size + x) as usize] = true;
             }
+
+            let free_cells: Vec<(i32, i32)> = (0..grid_size * grid_size)
+                .filter(|index| !occupied[*index as usize])
+                .map(|index| (index % grid_size, index / grid_size))
+                .collect();
+
+            self.apple = free_cells[self.rng.gen_range(0..free_cells.len())];
         }
 
         pub fn update(&mut self, args: &UpdateArgs) {
-            if self.snake.is_dead() {
+            if self.is_over() || self.paused {
                 return;
             }
 
             self.time_to_move -= args.dt;
 
             if self.time_to_move <= 0.0 {
-                self.turns += 1;
+                self.time_to_move = TIME_BETWEEN_MOVES / self.speed;
+                self.tick();
+            }
+        }
 
-                if self.snake.get_direction() != (-self.new_direction.0, -self.new_direction.1) {
-                    self.snake.change_direction(self.new_direction);
+        // advances the game by exactly one move, independent of the frame timing
+        pub fn tick(&mut self) {
+            if self.is_over() {
+                return;
+            }
+
+            // a queued press that would reverse the snake or keep it going the same way when it
+            // applies is skipped so it doesn't use up a tick
+            while let Some(direction) = self.input_queue.pop_front() {
+                let current = Direction::from_vector(self.snake.get_direction()).unwrap();
+
+                if direction != current && direction != current.opposite() {
+                    self.set_direction(direction);
+                    break;
                 }
+            }
+
+            self.turns += 1;
+            self.actions
+                .push(Direction::from_vector(self.new_direction).unwrap());
+
+            if self.snake.get_direction() != (-self.new_direction.0, -self.new_direction.1) {
+                self.snake.change_direction(self.new_direction);
+            }
+
+            // a move into the wall ends the game before the snake moves, so its head is always
+            // on the board
+            if !self.snake.is_inside(self.snake.get_next_head()) {
+                self.terminal_state = Some(TerminalState::HitWall);
+                return;
+            }
 
-                self.time_to_move = TIME_BETWEEN_MOVES;
+            if self.snake.get_next_head() == self.apple {
+                self.snake.update(true);
+                self.turns_since_apple = 0;
 
-                if self.snake.get_next_head() == self.apple {
-                    self.snake.update(true);
-                    self.chnage_apple_position();
-                } else {
-                    self.snake.update(false);
+                // nowhere left to put an apple, the player won
+                if self.snake.get_size() as u32 == self.config.grid_size * self.config.grid_size {
+                    self.terminal_state = Some(TerminalState::BoardFilled);
+                    return;
                 }
+
+                self.chnage_apple_position();
+            } else {
+                self.snake.update(false);
+                self.turns_since_apple += 1;
+            }
+
+            self.terminal_state = self.snake.get_collision();
+
+            if self.terminal_state.is_none()
+                && self
+                    .config
+                    .max_turns_without_apple
+                    .is_some_and(|max_turns| self.turns_since_apple >= max_turns)
+            {
+                self.terminal_state = Some(TerminalState::Starved);
+            }
+
+            if self.terminal_state.is_none()
+                && self
+                    .config
+                    .max_turns
+                    .is_some_and(|max_turns| self.turns >= max_turns)
+            {
+                self.terminal_state = Some(TerminalState::TurnLimit);
+            }
+        }
+
+        // how much of the time between the last move and the next one has passed, from 0 to 1,
+        // a paused game can be stepped without its timer so it is drawn on its cells
+        pub fn get_move_progress(&self) -> f64 {
+            if self.is_over() || self.paused {
+                return 1.0;
             }
+
+            (1.0 - self.time_to_move / (TIME_BETWEEN_MOVES / self.speed)).clamp(0.0, 1.0)
         }
 
         pub fn is_next_update_move(&self, dt: f64) -> bool {
             self.time_to_move - dt <= 0.0
         }
 
-        pub fn get_map(&self) -> Vec<Vec<MapItem>> {
-            let mut map = vec![vec![MapItem::Empty; GRID_SIZE as usize]; GRID_SIZE as usize];
-
-            for (x, y) in self.snake.get_snake() {
-                map[*y as usize][*x as usize] = MapItem::Snake;
+        pub fn get_state(&self) -> GameState {
+            GameState {
+                grid_size: self.config.grid_size,
+                snake: self.snake.get_snake().clone(),
+                direction: self.get_direction(),
+                apple: self.apple,
             }
+        }
 
-            map[self.apple.1 as usize][self.apple.0 as usize] = MapItem::Apple;
-
-            map
+        // the direction of the last move, not a new one set since
+        pub fn get_direction(&self) -> Direction {
+            Direction::from_vector(self.snake.get_direction()).unwrap()
         }
 
         pub fn get_snake_head(&self) -> (i32, i32) {
             self.snake.get_head()
         }
 
-        pub fn is_alive(&self) -> bool {
-            !self.snake.is_dead()
+        pub fn get_apple(&self) -> (i32, i32) {
+            self.apple
+        }
+
+        pub fn get_config(&self) -> &GameConfig {
+            &self.config
+        }
+
+        pub fn get_replay(&self) -> Replay {
+            Replay {
+                seed: self.seed,
+                config: self.config,
+                actions: self.actions.clone(),
+            }
+        }
+
+        pub fn is_over(&self) -> bool {
+            self.terminal_state.is_some()
+        }
+
+        pub fn set_panel_width(&mut self, panel_width: f64) {
+            self.panel_width = panel_width;
+        }
+
+        pub fn get_panel_width(&self) -> f64 {
+            self.panel_width
+        }
+
+        pub fn get_board_layout(&self, args: &RenderArgs) -> ([f64; 2], f64) {
+            get_board_layout(args, self.config.grid_size, self.panel_width)
+        }
+
+        pub fn get_speed(&self) -> f64 {
+            self.speed
+        }
+
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        pub fn get_terminal_state(&self) -> Option<TerminalState> {
+            self.terminal_state
+        }
+
+        pub fn is_won(&self) -> bool {
+            self.terminal_state == Some(TerminalState::BoardFilled)
         }
 
         pub fn get_score(&self) -> u32 {
@@ -151,4 +559,99 @@ pub mod game {
             self.turns
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn create_game(grid_size: u32) -> Game {
+            let config = GameConfig {
+                grid_size,
+                ..GameConfig::default()
+            };
+            Game::new_with(None, config, 0)
+        }
+
+        #[test]
+        fn wall_keeps_the_head_on_the_board() {
+            // the snake starts at the right edge of a 4x4 board going right
+            let mut game = create_game(4);
+            let before = game.get_state().snake;
+
+            game.tick();
+
+            assert_eq!(game.get_terminal_state(), Some(TerminalState::HitWall));
+            assert_eq!(game.get_state().snake, before);
+            assert!(game.get_state().is_inside(game.get_state().get_head()));
+        }
+
+        // the directions the snake went on the next `ticks` ticks
+        fn tick_directions(game: &mut Game, ticks: usize) -> Vec<Direction> {
+            (0..ticks)
+                .map(|_| {
+                    game.tick();
+                    game.get_direction()
+                })
+                .collect()
+        }
+
+        #[test]
+        fn fast_presses_apply_on_later_ticks() {
+            // the snake starts going right
+            let mut game = create_game(10);
+            let head = game.get_snake_head();
+
+            game.queue_direction(Direction::Up);
+            game.queue_direction(Direction::Left);
+
+            assert_eq!(
+                tick_directions(&mut game, 2),
+                vec![Direction::Up, Direction::Left]
+            );
+            assert_eq!(game.get_snake_head(), (head.0 - 1, head.1 - 1));
+        }
+
+        #[test]
+        fn queued_reversal_is_skipped() {
+            let mut game = create_game(10);
+
+            game.queue_direction(Direction::Left);
+            game.queue_direction(Direction::Up);
+
+            // left would reverse the snake when it applies, so up is used on the same tick
+            assert_eq!(tick_directions(&mut game, 1), vec![Direction::Up]);
+            assert!(!game.is_over());
+        }
+
+        #[test]
+        fn presses_over_the_limit_are_dropped() {
+            let mut game = create_game(10);
+            // up, left, up, ... one more than fits in the queue
+            let presses: Vec<Direction> = (0..=MAX_QUEUED_INPUTS)
+                .map(|i| [Direction::Up, Direction::Left][i % 2])
+                .collect();
+
+            for direction in &presses {
+                game.queue_direction(*direction);
+            }
+
+            // the last press was dropped, so the snake keeps going the same way
+            let mut expected = presses[..MAX_QUEUED_INPUTS].to_vec();
+            expected.push(expected[MAX_QUEUED_INPUTS - 1]);
+
+            assert_eq!(game.input_queue.len(), MAX_QUEUED_INPUTS);
+            assert_eq!(tick_directions(&mut game, MAX_QUEUED_INPUTS + 1), expected);
+        }
+
+        #[test]
+        fn repeated_presses_are_queued_once() {
+            let mut game = create_game(10);
+
+            game.queue_direction(Direction::Right);
+            game.queue_direction(Direction::Up);
+            game.queue_direction(Direction::Up);
+
+            assert_eq!(game.input_queue, VecDeque::from([Direction::Up]));
+        }
+    }
 }
@@ -0,0 +1,357 @@
+pub mod high_scores {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use crate::game::game::Game;
+
+    // entries kept for every board size and mode
+    const MAX_ENTRIES: usize = 10;
+    // entries shown when a game ends
+    const SHOWN_ENTRIES: usize = 5;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum PlayMode {
+        Human,
+        Ai,
+    }
+
+    impl PlayMode {
+        pub const ALL: [PlayMode; 2] = [PlayMode::Human, PlayMode::Ai];
+
+        pub fn get_name(self) -> &'static str {
+            match self {
+                PlayMode::Human => "human",
+                PlayMode::Ai => "ai",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<PlayMode> {
+            PlayMode::ALL
+                .into_iter()
+                .find(|mode| mode.get_name() == name)
+        }
+    }
+
+    #[derive(Debug, Clone)]
+    pub struct HighScore {
+        pub mode: PlayMode,
+        pub grid_size: u32,
+        pub score: u32,
+        pub turns: u32,
+        // yyyy-mm-dd
+        pub date: String,
+        pub name: String,
+    }
+
+    impl HighScore {
+        pub fn to_text(&self) -> String {
+            format!(
+                "{} by {}, {} turns, {}",
+                self.score, self.name, self.turns, self.date
+            )
+        }
+    }
+
+    // one entry per line: mode, grid size, score, turns, date and name separated by tabs
+    pub struct HighScores {
+        entries: Vec<HighScore>,
+    }
+
+    impl HighScores {
+        // a missing file is an empty table
+        pub fn new_from_file(path: &str) -> std::io::Result<HighScores> {
+            let text = match std::fs::read_to_string(path) {
+                Ok(text) => text,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(error) => return Err(error),
+            };
+
+            // lines that can't be read are skipped so one bad line doesn't lose the table
+            let entries = text
+                .lines()
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split('\t').collect();
+
+                    if fields.len() != 6 {
+                        return None;
+                    }
+
+                    Some(HighScore {
+                        mode: PlayMode::from_name(fields[0])?,
+                        grid_size: fields[1].parse().ok()?,
+                        score: fields[2].parse().ok()?,
+                        turns: fields[3].parse().ok()?,
+                        date: fields[4].to_string(),
+                        name: fields[5].to_string(),
+                    })
+                })
+                .collect();
+
+            Ok(HighScores { entries })
+        }
+
+        pub fn write_to_file(&self, path: &str) -> std::io::Result<()> {
+            let text: String = self
+                .entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{}\t{}\t{}\t{}\t{}\t{}\n",
+                        entry.mode.get_name(),
+                        entry.grid_size,
+                        entry.score,
+                        entry.turns,
+                        entry.date,
+                        entry.name
+                    )
+                })
+                .collect();
+
+            std::fs::write(path, text)
+        }
+
+        fn get_table_indices(&self, mode: PlayMode, grid_size: u32) -> Vec<usize> {
+            let mut indices: Vec<usize> = (0..self.entries.len())
+                .filter(|i| {
+                    self.entries[*i].mode == mode && self.entries[*i].grid_size == grid_size
+                })
+                .collect();
+
+            // best first, a score reached in fewer turns is better and ties keep the older entry
+            indices.sort_by(|a, b| {
+                let (a, b) = (&self.entries[*a], &self.entries[*b]);
+                b.score.cmp(&a.score).then(a.turns.cmp(&b.turns))
+            });
+
+            indices
+        }
+
+        pub fn get_table(&self, mode: PlayMode, grid_size: u32) -> Vec<&HighScore> {
+            self.get_table_indices(mode, grid_size)
+                .into_iter()
+                .map(|i| &self.entries[i])
+                .collect()
+        }
+
+        // returns the rank of the new entry (starting at 1) if it made it into the table
+        pub fn add(&mut self, entry: HighScore) -> Option<usize> {
+            let (mode, grid_size) = (entry.mode, entry.grid_size);
+            self.entries.push(entry);
+            let new_index = self.entries.len() - 1;
+
+            let mut table = self.get_table_indices(mode, grid_size);
+            let rank = table.iter().position(|i| *i == new_index).unwrap();
+            let dropped = table.split_off(MAX_ENTRIES.min(table.len()));
+
+            let mut index = 0;
+            self.entries.retain(|_| {
+                index += 1;
+                !dropped.contains(&(index - 1))
+            });
+
+            (rank < MAX_ENTRIES).then_some(rank + 1)
+        }
+
+        // every board size and mode that has entries
+        pub fn get_tables(&self) -> Vec<(PlayMode, u32)> {
+            let mut tables: Vec<(PlayMode, u32)> = Vec::new();
+
+            for entry in &self.entries {
+                if !tables.contains(&(entry.mode, entry.grid_size)) {
+                    tables.push((entry.mode, entry.grid_size));
+                }
+            }
+
+            tables.sort_by_key(|(mode, grid_size)| (*grid_size, mode.get_name()));
+            tables
+        }
+    }
+
+    // today's date in utc as yyyy-mm-dd
+    fn get_date() -> String {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() / 86400)
+            .unwrap_or(0) as i64;
+
+        get_date_from_days(days)
+    }
+
+    // converts days since 1970-01-01 to a civil date, eras are 400 year cycles
+    fn get_date_from_days(days: i64) -> String {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let day_of_era = z - era * 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    pub fn get_player_name() -> String {
+        std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .unwrap_or("player".to_string())
+    }
+
+    // records every game once, the first time it is seen over
+    pub struct ScoreKeeper {
+        path: String,
+        mode: PlayMode,
+        name: String,
+        recorded: bool,
+        lines: Vec<String>,
+    }
+
+    impl ScoreKeeper {
+        pub fn new(path: &str, mode: PlayMode, name: &str) -> ScoreKeeper {
+            ScoreKeeper {
+                path: path.to_string(),
+                mode,
+                // tabs and new lines would break the file
+                name: name.replace(['\t', '\n', '\r'], " "),
+                recorded: false,
+                lines: Vec::new(),
+            }
+        }
+
+        // returns true when the game was just recorded
+        pub fn update(&mut self, game: &Game) -> bool {
+            if !game.is_over() {
+                // the game was restarted
+                self.recorded = false;
+                self.lines.clear();
+                return false;
+            }
+
+            if self.recorded {
+                return false;
+            }
+
+            self.recorded = true;
+            self.lines = self.record(game);
+
+            true
+        }
+
+        fn record(&self, game: &Game) -> Vec<String> {
+            let mut high_scores = match HighScores::new_from_file(&self.path) {
+                Ok(high_scores) => high_scores,
+                Err(error) => return vec![format!("could not load high scores: {}", error)],
+            };
+
+            let grid_size = game.get_config().grid_size;
+            let rank = high_scores.add(HighScore {
+                mode: self.mode,
+                grid_size,
+                score: game.get_score(),
+                turns: game.get_turns(),
+                date: get_date(),
+                name: self.name.clone(),
+            });
+
+            let mut lines = vec![match rank {
+                Some(1) => "new high score!".to_string(),
+                Some(rank) => format!("rank {} in the high scores", rank),
+                None => "not in the high scores".to_string(),
+            }];
+
+            if let Err(error) = high_scores.write_to_file(&self.path) {
+                lines.push(format!("could not save high scores: {}", error));
+            }
+
+            lines.extend(
+                high_scores
+                    .get_table(self.mode, grid_size)
+                    .iter()
+                    .take(SHOWN_ENTRIES)
+                    .enumerate()
+                    .map(|(i, entry)| format!("{}. {}", i + 1, entry.to_text())),
+            );
+
+            lines
+        }
+
+        pub fn get_lines(&self) -> &[String] {
+            &self.lines
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn create_entry(score: u32, turns: u32, grid_size: u32) -> HighScore {
+            HighScore {
+                mode: PlayMode::Human,
+                grid_size,
+                score,
+                turns,
+                date: "2024-01-01".to_string(),
+                name: "player".to_string(),
+            }
+        }
+
+        #[test]
+        fn add_returns_rank() {
+            let mut high_scores = HighScores {
+                entries: Vec::new(),
+            };
+
+            assert_eq!(high_scores.add(create_entry(10, 100, 10)), Some(1));
+            assert_eq!(high_scores.add(create_entry(20, 100, 10)), Some(1));
+            assert_eq!(high_scores.add(create_entry(15, 100, 10)), Some(2));
+            // fewer turns beat the same score, more turns lose to it
+            assert_eq!(high_scores.add(create_entry(15, 50, 10)), Some(2));
+            assert_eq!(high_scores.add(create_entry(15, 200, 10)), Some(4));
+            // other boards have their own table
+            assert_eq!(high_scores.add(create_entry(1, 100, 20)), Some(1));
+        }
+
+        #[test]
+        fn add_trims_the_table() {
+            let mut high_scores = HighScores {
+                entries: Vec::new(),
+            };
+
+            for score in 1..=MAX_ENTRIES as u32 {
+                high_scores.add(create_entry(score, 100, 10));
+            }
+            high_scores.add(create_entry(5, 100, 20));
+
+            assert_eq!(high_scores.add(create_entry(0, 100, 10)), None);
+            assert_eq!(high_scores.add(create_entry(100, 100, 10)), Some(1));
+
+            let scores: Vec<u32> = high_scores
+                .get_table(PlayMode::Human, 10)
+                .iter()
+                .map(|entry| entry.score)
+                .collect();
+            assert_eq!(scores.len(), MAX_ENTRIES);
+            assert_eq!(scores[0], 100);
+            assert!(!scores.contains(&1));
+            assert_eq!(high_scores.get_table(PlayMode::Human, 20).len(), 1);
+        }
+
+        #[test]
+        fn date_from_days() {
+            assert_eq!(get_date_from_days(0), "1970-01-01");
+            assert_eq!(get_date_from_days(59), "1970-03-01");
+            assert_eq!(get_date_from_days(10957), "2000-01-01");
+            // 2000 is a leap year
+            assert_eq!(get_date_from_days(11016), "2000-02-29");
+            assert_eq!(get_date_from_days(19782), "2024-02-29");
+            assert_eq!(get_date_from_days(19783), "2024-03-01");
+            assert_eq!(get_date_from_days(20088), "2024-12-31");
+            assert_eq!(get_date_from_days(-1), "1969-12-31");
+        }
+    }
+}
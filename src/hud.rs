@@ -0,0 +1,213 @@
+pub mod hud {
+    use opengl_graphics::{GlGraphics, GlyphCache, TextureSettings};
+    use piston::RenderArgs;
+
+    use crate::{
+        game::game::Game,
+        key_bindings::key_bindings::{Command, KeyBindings},
+        theme::theme::{Color, Theme},
+    };
+
+    // the font is embedded so the binary works from any folder
+    const FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+    const FONT_SIZE: u32 = 20;
+    const LINE_HEIGHT: f64 = 26.0;
+    const PADDING: f64 = 8.0;
+    const BAR_WIDTH: f64 = 120.0;
+
+    // text drawn over the top left corner of the board
+    pub struct Hud {
+        glyphs: GlyphCache<'static>,
+        text_color: Color,
+        background: Color,
+        highlight: Color,
+    }
+
+    impl Hud {
+        pub fn new(theme: &Theme) -> Hud {
+            Hud {
+                glyphs: GlyphCache::from_bytes(FONT, (), TextureSettings::new()).unwrap(),
+                text_color: theme.text,
+                background: theme.text_background,
+                highlight: theme.head,
+            }
+        }
+
+        pub fn render(&mut self, args: &RenderArgs, gl: &mut GlGraphics, lines: &[String]) {
+            self.draw_lines(args, gl, lines, false);
+        }
+
+        // same as render but in the middle of the window
+        pub fn render_overlay(&mut self, args: &RenderArgs, gl: &mut GlGraphics, lines: &[String]) {
+            self.draw_lines(args, gl, lines, true);
+        }
+
+        // one bar per value at the top with its right side at `right`, values go from 0 to 1
+        // and the chosen one is drawn in the highlight color
+        pub fn render_bars(
+            &mut self,
+            args: &RenderArgs,
+            gl: &mut GlGraphics,
+            bars: &[(&str, f64)],
+            chosen: usize,
+            right: f64,
+        ) {
+            use graphics::*;
+
+            let (text_color, background, highlight) =
+                (self.text_color, self.background, self.highlight);
+            let glyphs = &mut self.glyphs;
+
+            let label_width = bars
+                .iter()
+                .map(|(label, _)| glyphs.width(FONT_SIZE, label).unwrap_or(0.0))
+                .fold(0.0, f64::max)
+                + PADDING;
+            let width = label_width + BAR_WIDTH + PADDING * 2.0;
+            let height = bars.len() as f64 * LINE_HEIGHT + PADDING;
+            let x = right - width;
+
+            gl.draw(args.viewport(), |context, gl| {
+                let transform = context.transform.trans(x, 0.0);
+
+                rectangle(background, [0.0, 0.0, width, height], transform, gl);
+
+                for (i, (label, value)) in bars.iter().enumerate() {
+                    let color = if i == chosen { highlight } else { text_color };
+                    let line_transform = transform.trans(PADDING, (i + 1) as f64 * LINE_HEIGHT);
+
+                    text(color, FONT_SIZE, label, glyphs, line_transform, gl).unwrap();
+
+                    let top = i as f64 * LINE_HEIGHT + PADDING * 1.5;
+                    let bar_height = LINE_HEIGHT - PADDING;
+
+                    rectangle(
+                        background,
+                        [PADDING + label_width, top, BAR_WIDTH, bar_height],
+                        transform,
+                        gl,
+                    );
+                    rectangle(
+                        color,
+                        [
+                            PADDING + label_width,
+                            top,
+                            BAR_WIDTH * value.clamp(0.0, 1.0),
+                            bar_height,
+                        ],
+                        transform,
+                        gl,
+                    );
+                }
+            });
+        }
+
+        fn draw_lines(
+            &mut self,
+            args: &RenderArgs,
+            gl: &mut GlGraphics,
+            lines: &[String],
+            centered: bool,
+        ) {
+            use graphics::*;
+
+            let (text_color, background) = (self.text_color, self.background);
+            let glyphs = &mut self.glyphs;
+
+            let width = lines
+                .iter()
+                .map(|line| glyphs.width(FONT_SIZE, line).unwrap_or(0.0))
+                .fold(0.0, f64::max)
+                + PADDING * 2.0;
+            let height = lines.len() as f64 * LINE_HEIGHT + PADDING;
+
+            let (x, y) = if centered {
+                (
+                    (args.window_size[0] - width) / 2.0,
+                    (args.window_size[1] - height) / 2.0,
+                )
+            } else {
+                (0.0, 0.0)
+            };
+
+            gl.draw(args.viewport(), |context, gl| {
+                let transform = context.transform.trans(x, y);
+
+                rectangle(background, [0.0, 0.0, width, height], transform, gl);
+
+                for (i, line) in lines.iter().enumerate() {
+                    // text is positioned by its baseline
+                    let line_transform = transform.trans(PADDING, (i + 1) as f64 * LINE_HEIGHT);
+
+                    text(text_color, FONT_SIZE, line, glyphs, line_transform, gl).unwrap();
+                }
+            });
+        }
+    }
+
+    pub fn get_game_over_message(game: &Game) -> String {
+        if game.is_won() {
+            format!("you win! score {}", game.get_score())
+        } else {
+            format!(
+                "game over ({}), score {}",
+                game.get_terminal_state().unwrap().get_name(),
+                game.get_score()
+            )
+        }
+    }
+
+    // the lines shown in the middle of the board when a human game is paused or over
+    pub fn get_overlay(game: &Game, key_bindings: &KeyBindings) -> Option<Vec<String>> {
+        if game.is_over() {
+            Some(vec![
+                get_game_over_message(game),
+                format!(
+                    "press {} to restart",
+                    key_bindings.get_keys_text(Command::Restart)
+                ),
+            ])
+        } else if game.is_paused() {
+            Some(vec![
+                "paused".to_string(),
+                format!(
+                    "press {} to resume",
+                    key_bindings.get_keys_text(Command::Pause)
+                ),
+            ])
+        } else {
+            None
+        }
+    }
+
+    // the same for an ai game, which can also be stepped while paused
+    pub fn get_ai_overlay(game: &Game, key_bindings: &KeyBindings) -> Option<Vec<String>> {
+        if game.is_paused() {
+            Some(vec![
+                "paused".to_string(),
+                format!(
+                    "press {} to resume or {} to make one move",
+                    key_bindings.get_keys_text(Command::Pause),
+                    key_bindings.get_keys_text(Command::Right)
+                ),
+            ])
+        } else {
+            get_overlay(game, key_bindings)
+        }
+    }
+
+    pub fn get_stats(game: &Game) -> String {
+        let mut stats = format!(
+            "score {}  length {}  turns {}",
+            game.get_score(),
+            game.get_state().snake.len(),
+            game.get_turns()
+        );
+
+        if let Some(terminal_state) = game.get_terminal_state() {
+            stats += &format!("  {}", terminal_state.get_name());
+        }
+
+        stats
+    }
+}
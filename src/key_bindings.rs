@@ -0,0 +1,240 @@
+pub mod key_bindings {
+    use piston::Key;
+
+    use crate::config::config::Config;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Command {
+        Up,
+        Down,
+        Left,
+        Right,
+        Pause,
+        Restart,
+        SpeedUp,
+        SpeedDown,
+        Turbo,
+        Sensors,
+        Network,
+        Fullscreen,
+        Quit,
+    }
+
+    impl Command {
+        pub const ALL: [Command; 13] = [
+            Command::Up,
+            Command::Down,
+            Command::Left,
+            Command::Right,
+            Command::Pause,
+            Command::Restart,
+            Command::SpeedUp,
+            Command::SpeedDown,
+            Command::Turbo,
+            Command::Sensors,
+            Command::Network,
+            Command::Fullscreen,
+            Command::Quit,
+        ];
+
+        // the name used in the config file, as `key.<name>`
+        pub fn get_name(self) -> &'static str {
+            match self {
+                Command::Up => "up",
+                Command::Down => "down",
+                Command::Left => "left",
+                Command::Right => "right",
+                Command::Pause => "pause",
+                Command::Restart => "restart",
+                Command::SpeedUp => "speed_up",
+                Command::SpeedDown => "speed_down",
+                Command::Turbo => "turbo",
+                Command::Sensors => "sensors",
+                Command::Network => "network",
+                Command::Fullscreen => "fullscreen",
+                Command::Quit => "quit",
+            }
+        }
+    }
+
+    // names that can be used in the config file, the first name of a key is the one shown
+    const KEY_NAMES: [(&str, Key); 55] = [
+        ("up", Key::Up),
+        ("down", Key::Down),
+        ("left", Key::Left),
+        ("right", Key::Right),
+        ("space", Key::Space),
+        ("enter", Key::Return),
+        ("esc", Key::Escape),
+        ("backspace", Key::Backspace),
+        ("tab", Key::Tab),
+        ("home", Key::Home),
+        ("end", Key::End),
+        ("pageup", Key::PageUp),
+        ("pagedown", Key::PageDown),
+        ("f11", Key::F11),
+        ("plus", Key::Equals),
+        ("minus", Key::Minus),
+        ("+", Key::Equals),
+        ("-", Key::Minus),
+        ("=", Key::Equals),
+        ("a", Key::A),
+        ("b", Key::B),
+        ("c", Key::C),
+        ("d", Key::D),
+        ("e", Key::E),
+        ("f", Key::F),
+        ("g", Key::G),
+        ("h", Key::H),
+        ("i", Key::I),
+        ("j", Key::J),
+        ("k", Key::K),
+        ("l", Key::L),
+        ("m", Key::M),
+        ("n", Key::N),
+        ("o", Key::O),
+        ("p", Key::P),
+        ("q", Key::Q),
+        ("r", Key::R),
+        ("s", Key::S),
+        ("t", Key::T),
+        ("u", Key::U),
+        ("v", Key::V),
+        ("w", Key::W),
+        ("x", Key::X),
+        ("y", Key::Y),
+        ("z", Key::Z),
+        ("0", Key::D0),
+        ("1", Key::D1),
+        ("2", Key::D2),
+        ("3", Key::D3),
+        ("4", Key::D4),
+        ("5", Key::D5),
+        ("6", Key::D6),
+        ("7", Key::D7),
+        ("8", Key::D8),
+        ("9", Key::D9),
+    ];
+
+    pub fn get_key_name(key: Key) -> &'static str {
+        KEY_NAMES
+            .iter()
+            .find(|(_, named_key)| *named_key == key)
+            .map(|(name, _)| *name)
+            .unwrap_or("?")
+    }
+
+    fn parse_key(name: &str) -> Option<Key> {
+        KEY_NAMES
+            .iter()
+            .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+            .map(|(_, key)| *key)
+    }
+
+    // every key maps to at most one command, a command can have many keys
+    #[derive(Debug, Clone)]
+    pub struct KeyBindings {
+        bindings: Vec<(Key, Command)>,
+    }
+
+    impl Default for KeyBindings {
+        // arrows, wasd and vim keys move
+        fn default() -> KeyBindings {
+            let bindings = vec![
+                (Key::Up, Command::Up),
+                (Key::W, Command::Up),
+                (Key::K, Command::Up),
+                (Key::Down, Command::Down),
+                (Key::S, Command::Down),
+                (Key::J, Command::Down),
+                (Key::Left, Command::Left),
+                (Key::A, Command::Left),
+                (Key::H, Command::Left),
+                (Key::Right, Command::Right),
+                (Key::D, Command::Right),
+                (Key::L, Command::Right),
+                (Key::P, Command::Pause),
+                (Key::Space, Command::Pause),
+                (Key::R, Command::Restart),
+                (Key::Equals, Command::SpeedUp),
+                (Key::Minus, Command::SpeedDown),
+                (Key::T, Command::Turbo),
+                (Key::V, Command::Sensors),
+                (Key::N, Command::Network),
+                (Key::F11, Command::Fullscreen),
+                (Key::F, Command::Fullscreen),
+                (Key::Escape, Command::Quit),
+                (Key::Q, Command::Quit),
+            ];
+
+            KeyBindings { bindings }
+        }
+    }
+
+    impl KeyBindings {
+        // `key.<command> = key, key, ...` replaces all the keys of that command, key names are
+        // the ones in KEY_NAMES,
+        // commands that aren't in the config keep their default keys
+        pub fn new_from_config(config: &Config) -> Result<KeyBindings, String> {
+            let mut key_bindings = KeyBindings::default();
+
+            for name in config.get_names() {
+                if let Some(command_name) = name.strip_prefix("key.") {
+                    if !Command::ALL
+                        .iter()
+                        .any(|command| command.get_name() == command_name)
+                    {
+                        return Err(format!("unknown command in {}", name));
+                    }
+                }
+            }
+
+            for command in Command::ALL {
+                let keys = match config.get(&format!("key.{}", command.get_name())) {
+                    Some(keys) => keys,
+                    None => continue,
+                };
+
+                key_bindings
+                    .bindings
+                    .retain(|(_, bound_command)| *bound_command != command);
+
+                // an empty list leaves the command without keys
+                for key_name in keys
+                    .split(',')
+                    .map(|key_name| key_name.trim())
+                    .filter(|key_name| !key_name.is_empty())
+                {
+                    let key = parse_key(key_name).ok_or(format!("unknown key {}", key_name))?;
+
+                    // the key is taken away from the command it had by default
+                    key_bindings
+                        .bindings
+                        .retain(|(bound_key, _)| *bound_key != key);
+                    key_bindings.bindings.push((key, command));
+                }
+            }
+
+            Ok(key_bindings)
+        }
+
+        pub fn get_command(&self, key: Key) -> Option<Command> {
+            self.bindings
+                .iter()
+                .find(|(bound_key, _)| *bound_key == key)
+                .map(|(_, command)| *command)
+        }
+
+        // "p or space", used to tell the player what to press
+        pub fn get_keys_text(&self, command: Command) -> String {
+            let names: Vec<&str> = self
+                .bindings
+                .iter()
+                .filter(|(_, bound_command)| *bound_command == command)
+                .map(|(key, _)| get_key_name(*key))
+                .collect();
+
+            names.join(" or ")
+        }
+    }
+}
@@ -1,105 +1,695 @@
+extern crate glutin;
 extern crate glutin_window;
 extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
 
 use crate::piston::PressEvent;
+use crate::piston::Window as _;
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
 use piston::input::Button;
 use piston::input::{RenderEvent, UpdateEvent};
-use piston::window::WindowSettings;
+use piston::window::{AdvancedWindow, WindowSettings};
 
+mod agents;
 mod ai;
+mod config;
 mod constants;
+mod evaluation;
+mod export;
 mod game;
+mod high_scores;
+mod hud;
+mod key_bindings;
+mod network_view;
+mod replay;
+mod replay_viewer;
+mod sensors;
 mod snake;
+mod terminal;
+#[cfg(test)]
+mod test_utils;
+mod theme;
+use agents::agent::agent::{create_agent, AGENT_NAMES};
+use agents::neural_agent::neural_agent::{
+    get_activation_names, get_choice, NeuralAgent, DEFAULT_LAYOUT,
+};
+use ai::actions::actions::ActionSpace;
 use ai::ai_game::ai_game::AiGame;
+use ai::environment::environment::create_reward_function;
+use ai::observation::observation::{DEFAULT_ENCODER, ENCODER_NAMES};
 use ai::train_netwrok::train_network::*;
+use config::config::Config;
 use constants::constants::*;
-use game::game::Game;
+use evaluation::evaluation::{evaluate, record_game, EvaluationReport};
+use game::game::{Game, GameConfig};
+use high_scores::high_scores::{get_player_name, HighScores, PlayMode, ScoreKeeper};
+use hud::hud::{get_ai_overlay, get_game_over_message, get_overlay, get_stats, Hud};
+use key_bindings::key_bindings::{Command, KeyBindings};
+use replay::replay::{is_replay_file, Replay};
+use replay_viewer::replay_viewer::ReplayViewer;
+use sensors::sensors::get_output_bars;
+use terminal::terminal::{ScoredView, TerminalView};
+use theme::theme::Theme;
 
-fn play() {
-    let opengl = OpenGL::V3_2;
+const OPENGL: OpenGL = OpenGL::V3_2;
 
-    let mut window: Window = WindowSettings::new("snake", [WINDOW_SIZE, WINDOW_SIZE])
-        .graphics_api(opengl)
-        .exit_on_esc(true)
+// how games are shown, from the command line and snake.cfg
+struct Settings {
+    terminal: bool,
+    fullscreen: bool,
+    // draw the snake between cells instead of jumping from cell to cell
+    smooth: bool,
+    key_bindings: KeyBindings,
+    theme: Theme,
+}
+
+// the window can be resized, the board is scaled to fit it
+fn create_window(title: &str, fullscreen: bool) -> Window {
+    WindowSettings::new(title, [WINDOW_SIZE, WINDOW_SIZE])
+        .graphics_api(OPENGL)
+        .exit_on_esc(false)
+        .resizable(true)
+        .fullscreen(fullscreen)
         .build()
-        .unwrap();
+        .unwrap()
+}
+
+fn toggle_fullscreen(window: &Window) {
+    let window = window.ctx.window();
+
+    if window.fullscreen().is_some() {
+        window.set_fullscreen(None);
+    } else {
+        window.set_fullscreen(Some(glutin::window::Fullscreen::Borderless(None)));
+    }
+}
+
+fn get_speed_text(speed: f64) -> String {
+    format!("speed {} moves/s", speed / TIME_BETWEEN_MOVES)
+}
+
+fn run_in_terminal(view: &mut dyn TerminalView, key_bindings: &KeyBindings) {
+    if let Err(error) = terminal::terminal::run(view, key_bindings) {
+        println!("terminal error: {}", error);
+    }
+
+    if view.get_game().is_over() {
+        println!("{}", get_game_over_message(view.get_game()));
+    }
+}
+
+fn run_scored_in_terminal<V: TerminalView>(
+    view: V,
+    keeper: ScoreKeeper,
+    key_bindings: &KeyBindings,
+) {
+    let mut view = ScoredView { view, keeper };
+    run_in_terminal(&mut view, key_bindings);
+
+    for line in view.keeper.get_lines() {
+        println!("{}", line);
+    }
+}
 
-    let mut game = Game::new(Some(GlGraphics::new(opengl)));
+fn play(settings: &Settings) {
+    let mut keeper = ScoreKeeper::new(HIGH_SCORES_FILE, PlayMode::Human, &get_player_name());
+
+    if settings.terminal {
+        run_scored_in_terminal(Game::new(None), keeper, &settings.key_bindings);
+        return;
+    }
+
+    let mut window = create_window("snake", settings.fullscreen);
+
+    let mut game = Game::new(Some(GlGraphics::new(OPENGL)));
+    let mut hud = Hud::new(&settings.theme);
+
+    let mut game_over_shown = false;
 
     let mut events = Events::new(EventSettings::new());
     while let Some(event) = events.next(&mut window) {
         if let Some(Button::Keyboard(key)) = event.press_args() {
-            game.handle_input(key);
+            match settings.key_bindings.get_command(key) {
+                Some(Command::Quit) => window.set_should_close(true),
+                Some(Command::Fullscreen) => toggle_fullscreen(&window),
+                Some(command) => game.handle_command(command),
+                None => {}
+            }
         }
 
         if let Some(args) = event.render_args() {
-            game.render(&args);
+            let progress = if settings.smooth {
+                game.get_move_progress()
+            } else {
+                1.0
+            };
+            game.render(&args, &settings.theme, progress);
+
+            let lines = [get_stats(&game), get_speed_text(game.get_speed())];
+            hud.render(&args, game.gl.as_mut().unwrap(), &lines);
+
+            if let Some(mut lines) = get_overlay(&game, &settings.key_bindings) {
+                lines.extend_from_slice(keeper.get_lines());
+                hud.render_overlay(&args, game.gl.as_mut().unwrap(), &lines);
+            }
         }
 
         if let Some(args) = event.update_args() {
             game.update(&args);
+
+            if keeper.update(&game) {
+                for line in keeper.get_lines() {
+                    println!("{}", line);
+                }
+            }
+
+            if game.is_over() && !game_over_shown {
+                let message = get_game_over_message(&game);
+                println!("{}", message);
+                window.set_title(format!("snake - {}", message));
+                game_over_shown = true;
+            }
+
+            // the game was restarted
+            if !game.is_over() && game_over_shown {
+                window.set_title("snake".to_string());
+                game_over_shown = false;
+            }
         }
     }
 }
 
-fn load_trained() {
+fn load_trained(settings: &Settings) {
     println!("enter network path:");
-    let mut path = String::new();
-    std::io::stdin().read_line(&mut path);
-    path = path.trim().to_string();
+    let path = read_input();
 
-    let opengl = OpenGL::V3_2;
+    let agent = match NeuralAgent::new_from_file(&path) {
+        Ok(agent) => agent,
+        Err(error) => {
+            println!("could not load the network: {}", error);
+            return;
+        }
+    };
 
-    let mut window: Window = WindowSettings::new("ai snake", [WINDOW_SIZE, WINDOW_SIZE])
-        .graphics_api(opengl)
-        .exit_on_esc(true)
-        .build()
-        .unwrap();
+    let model_name = std::path::Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or(path.clone());
+    let model_text = match read_fitness(&path) {
+        Some(fitness) => format!("model {}  fitness {:.2}", model_name, fitness),
+        None => format!("model {}  fitness unknown", model_name),
+    };
 
-    use crate::ai::neural_network::neural_network::NeuralNetwork;
-    let network = NeuralNetwork::new_from_file(vec![24, 40, 40, 4], &path);
-    let mut ai_game = AiGame::new_from(Some(GlGraphics::new(opengl)), &network);
+    let mut keeper = ScoreKeeper::new(HIGH_SCORES_FILE, PlayMode::Ai, &model_name);
+
+    if settings.terminal {
+        run_scored_in_terminal(
+            AiGame::new_from(None, GameConfig::default(), &agent),
+            keeper,
+            &settings.key_bindings,
+        );
+        return;
+    }
+
+    let mut window = create_window("ai snake", settings.fullscreen);
+
+    let mut ai_game =
+        AiGame::new_from(Some(GlGraphics::new(OPENGL)), GameConfig::default(), &agent);
+    let mut hud = Hud::new(&settings.theme);
+
+    let mut game_over_shown = false;
 
     let mut events = Events::new(EventSettings::new());
     while let Some(event) = events.next(&mut window) {
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            match settings.key_bindings.get_command(key) {
+                Some(Command::Quit) => window.set_should_close(true),
+                Some(Command::Fullscreen) => toggle_fullscreen(&window),
+                Some(command) => ai_game.handle_command(command),
+                None => {}
+            }
+        }
+
         if let Some(args) = event.render_args() {
-            ai_game.render(&args);
+            ai_game.render(&args, &settings.theme, settings.smooth);
+
+            let speed_text = if ai_game.is_turbo() {
+                "speed unlimited (turbo)".to_string()
+            } else {
+                get_speed_text(ai_game.get_game().get_speed())
+            };
+            let lines = [
+                get_stats(ai_game.get_game()),
+                speed_text,
+                model_text.clone(),
+            ];
+            hud.render(&args, ai_game.get_game_mut().gl.as_mut().unwrap(), &lines);
+
+            if ai_game.is_showing_sensors() {
+                let outputs = ai_game.get_outputs();
+                let bars = get_output_bars(&outputs, ai_game.get_agent().get_action_space());
+                // left of the network panel when it is shown
+                let right = args.window_size[0] - ai_game.get_game().get_panel_width();
+
+                let gl = ai_game.get_game_mut().gl.as_mut().unwrap();
+                hud.render_bars(&args, gl, &bars, get_choice(&outputs), right);
+            }
+
+            if let Some(mut lines) = get_ai_overlay(ai_game.get_game(), &settings.key_bindings) {
+                lines.extend_from_slice(keeper.get_lines());
+                let gl = ai_game.get_game_mut().gl.as_mut().unwrap();
+                hud.render_overlay(&args, gl, &lines);
+            }
         }
 
         if let Some(args) = event.update_args() {
             ai_game.update(&args);
+
+            if keeper.update(ai_game.get_game()) {
+                for line in keeper.get_lines() {
+                    println!("{}", line);
+                }
+            }
+
+            if ai_game.get_game().is_over() && !game_over_shown {
+                let message = get_game_over_message(ai_game.get_game());
+                println!("{}", message);
+                window.set_title(format!("ai snake - {}", message));
+                game_over_shown = true;
+            }
+
+            // the game was restarted
+            if !ai_game.get_game().is_over() && game_over_shown {
+                window.set_title("ai snake".to_string());
+                game_over_shown = false;
+            }
+        }
+    }
+}
+
+fn watch_replay(path: &str, settings: &Settings) {
+    let replay = match Replay::new_from_file(path) {
+        Ok(replay) => replay,
+        Err(error) => {
+            println!("could not load replay: {}", error);
+            return;
+        }
+    };
+
+    if settings.terminal {
+        run_in_terminal(&mut ReplayViewer::new(None, replay), &settings.key_bindings);
+        return;
+    }
+
+    let mut window = create_window("replay", settings.fullscreen);
+
+    let mut viewer = ReplayViewer::new(Some(GlGraphics::new(OPENGL)), replay);
+    let mut hud = Hud::new(&settings.theme);
+    let mut title = String::new();
+
+    let mut events = Events::new(EventSettings::new());
+    while let Some(event) = events.next(&mut window) {
+        if let Some(Button::Keyboard(key)) = event.press_args() {
+            match settings.key_bindings.get_command(key) {
+                Some(Command::Quit) => window.set_should_close(true),
+                Some(Command::Fullscreen) => toggle_fullscreen(&window),
+                Some(command) => viewer.handle_command(command),
+                None => viewer.handle_key(key),
+            }
+        }
+
+        if let Some(args) = event.render_args() {
+            viewer.render(&args, &settings.theme, settings.smooth);
+
+            let lines = [
+                get_stats(viewer.get_game()),
+                get_speed_text(viewer.get_speed()),
+            ];
+            hud.render(&args, viewer.get_game_mut().gl.as_mut().unwrap(), &lines);
+        }
+
+        if let Some(args) = event.update_args() {
+            viewer.update(&args);
+
+            if viewer.get_title() != title {
+                title = viewer.get_title();
+                window.set_title(title.clone());
+            }
+        }
+    }
+}
+
+// a line typed by the user without the line break, empty if stdin can't be read
+fn read_input() -> String {
+    let mut input = String::new();
+    if let Err(error) = std::io::stdin().read_line(&mut input) {
+        println!("could not read the input: {}", error);
+    }
+    input.trim().to_string()
+}
+
+fn parse_grid_size(value: &str) -> Result<u32, String> {
+    let grid_size: u32 = value.parse().map_err(|_| "invalid --grid")?;
+    if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&grid_size) {
+        return Err(format!(
+            "--grid must be between {} and {}",
+            MIN_GRID_SIZE, MAX_GRID_SIZE
+        ));
+    }
+    Ok(grid_size)
+}
+
+// usage: eval <agent name or network path> [--games N] [--seed N] [--grid N] [--no-limits]
+//             [--reward apple|distance] [--json path]
+// --no-limits only removes the turn limit, a snake that doesn't eat still starves so an agent
+// that loops can't make it run forever
+fn eval(args: &[String]) -> Result<(), String> {
+    let mut agent_name: Option<&str> = None;
+    let mut games = 100;
+    let mut seed = 0;
+    let mut config = GameConfig::training();
+    let mut json_path: Option<&str> = None;
+    let mut reward = "apple";
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(|value| value.as_str())
+                .ok_or(format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--games" => games = value()?.parse().map_err(|_| "invalid --games")?,
+            "--seed" => seed = value()?.parse().map_err(|_| "invalid --seed")?,
+            "--grid" => config.grid_size = parse_grid_size(value()?)?,
+            "--json" => json_path = Some(value()?),
+            "--reward" => reward = value()?,
+            "--no-limits" => config.max_turns = None,
+            name => agent_name = Some(name),
+        }
+    }
+
+    let agent_name = agent_name.ok_or(format!(
+        "missing agent, use a network path or one of: {}",
+        AGENT_NAMES.join(", ")
+    ))?;
+    let reward_function = create_reward_function(reward)?;
+
+    if games == 0 {
+        return Err("--games must be at least 1".to_string());
+    }
+
+    let mut agent = create_agent(agent_name, config.grid_size)?;
+    let report = EvaluationReport::new(&evaluate(
+        agent.as_mut(),
+        config,
+        games,
+        seed,
+        reward_function,
+    ));
+
+    print!("{}", report.to_text());
+
+    if let Some(json_path) = json_path {
+        std::fs::write(json_path, report.to_json()).map_err(|error| error.to_string())?;
+    }
+
+    Ok(())
+}
+
+// usage: export <replay file, agent name or network path> [--gif path | --frames folder]
+//               [--cell N] [--delay N] [--seed N] [--grid N] [--no-limits]
+// agents play a new game with the given seed, replays are exported as they were recorded
+// --no-limits only removes the turn limit like in eval
+fn export(args: &[String], theme: &Theme) -> Result<(), String> {
+    let mut source: Option<&str> = None;
+    let mut gif_path: Option<&str> = None;
+    let mut frames_folder: Option<&str> = None;
+    let mut cell_size = 10;
+    let mut delay = 20;
+    let mut seed = 0;
+    let mut config = GameConfig::training();
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(|value| value.as_str())
+                .ok_or(format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--gif" => gif_path = Some(value()?),
+            "--frames" => frames_folder = Some(value()?),
+            "--cell" => cell_size = value()?.parse().map_err(|_| "invalid --cell")?,
+            "--delay" => delay = value()?.parse().map_err(|_| "invalid --delay")?,
+            "--seed" => seed = value()?.parse().map_err(|_| "invalid --seed")?,
+            "--grid" => config.grid_size = parse_grid_size(value()?)?,
+            "--no-limits" => config.max_turns = None,
+            name => source = Some(name),
+        }
+    }
+
+    let source = source.ok_or(format!(
+        "missing replay file, network path or agent name ({})",
+        AGENT_NAMES.join(", ")
+    ))?;
+
+    if gif_path.is_none() && frames_folder.is_none() {
+        return Err("use --gif and / or --frames to choose an output".to_string());
+    }
+
+    if cell_size == 0 {
+        return Err("--cell must be at least 1".to_string());
+    }
+
+    let replay = if is_replay_file(source) {
+        Replay::new_from_file(source).map_err(|error| error.to_string())?
+    } else {
+        let mut agent = create_agent(source, config.grid_size)?;
+        record_game(agent.as_mut(), config, seed)
+    };
+
+    if let Some(gif_path) = gif_path {
+        export::export::export_gif(&replay, gif_path, cell_size, delay, theme)
+            .map_err(|error| error.to_string())?;
+        println!("saved {}", gif_path);
+    }
+
+    if let Some(frames_folder) = frames_folder {
+        export::export::export_frames(&replay, frames_folder, cell_size, theme)
+            .map_err(|error| error.to_string())?;
+        println!(
+            "saved {} frames in {}",
+            replay.get_tick_count() + 1,
+            frames_folder
+        );
+    }
+
+    Ok(())
+}
+
+// usage: highscores [--grid N] [--mode human|ai]
+fn show_high_scores(args: &[String]) -> Result<(), String> {
+    let mut grid_size: Option<u32> = None;
+    let mut mode: Option<PlayMode> = None;
+
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(|value| value.as_str())
+                .ok_or(format!("missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--grid" => grid_size = Some(value()?.parse().map_err(|_| "invalid --grid")?),
+            "--mode" => mode = Some(PlayMode::from_name(value()?).ok_or("invalid --mode")?),
+            other => return Err(format!("unknown argument {}", other)),
         }
     }
+
+    let high_scores = HighScores::new_from_file(HIGH_SCORES_FILE)
+        .map_err(|error| format!("could not load high scores: {}", error))?;
+
+    let tables: Vec<(PlayMode, u32)> = high_scores
+        .get_tables()
+        .into_iter()
+        .filter(|(table_mode, table_grid_size)| {
+            mode.is_none_or(|mode| mode == *table_mode)
+                && grid_size.is_none_or(|grid_size| grid_size == *table_grid_size)
+        })
+        .collect();
+
+    if tables.is_empty() {
+        println!("no high scores yet");
+    }
+
+    for (mode, grid_size) in tables {
+        println!("{} {}x{}:", mode.get_name(), grid_size, grid_size);
+
+        for (i, entry) in high_scores.get_table(mode, grid_size).iter().enumerate() {
+            println!("  {}. {}", i + 1, entry.to_text());
+        }
+    }
+
+    Ok(())
+}
+
+// a broken config is reported and the defaults are used, so the game still starts
+fn load_config() -> Config {
+    Config::new_from_file(CONFIG_FILE).unwrap_or_else(|error| {
+        println!("{}, using the default settings", error);
+        Config::default()
+    })
+}
+
+fn load_key_bindings(config: &Config) -> KeyBindings {
+    KeyBindings::new_from_config(config).unwrap_or_else(|error| {
+        println!("{} in {}, using the default keys", error, CONFIG_FILE);
+        KeyBindings::default()
+    })
+}
+
+fn load_theme(config: &Config) -> Theme {
+    Theme::new_from_config(config).unwrap_or_else(|error| {
+        println!("{} in {}, using the light theme", error, CONFIG_FILE);
+        Theme::light()
+    })
 }
 
 fn main() {
-    let mut option = String::new();
-    println!("1: play\n2: load trained\n3: train");
-    std::io::stdin().read_line(&mut option);
-    option = option.trim().to_string();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let config = load_config();
+
+    // --terminal draws the game in the terminal instead of opening a window
+    let terminal = args.iter().any(|arg| arg == "--terminal");
+    args.retain(|arg| arg != "--terminal");
+
+    let settings = Settings {
+        terminal,
+        fullscreen: config.get("fullscreen") == Some("true"),
+        smooth: config.get("smooth") == Some("true"),
+        key_bindings: load_key_bindings(&config),
+        theme: load_theme(&config),
+    };
+
+    match args.first().map(|arg| arg.as_str()) {
+        Some("eval") => {
+            if let Err(error) = eval(&args[1..]) {
+                println!("{}", error);
+            }
+            return;
+        }
+        Some("export") => {
+            if let Err(error) = export(&args[1..], &settings.theme) {
+                println!("{}", error);
+            }
+            return;
+        }
+        Some("highscores") => {
+            if let Err(error) = show_high_scores(&args[1..]) {
+                println!("{}", error);
+            }
+            return;
+        }
+        Some("replay") => {
+            match args.get(1) {
+                Some(path) => watch_replay(path, &settings),
+                None => println!("usage: replay <replay file> [--terminal]"),
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    println!("1: play\n2: load trained\n3: train\n4: evaluate\n5: watch replay\n6: high scores");
+    let option = read_input();
 
     if option.eq("1") {
-        play();
+        play(&settings);
     } else if option.eq("2") {
-        load_trained();
+        load_trained(&settings);
     } else if option.eq("3") {
         println!("enter save folder path:");
-        let mut save_path = String::new();
-        std::io::stdin().read_line(&mut save_path);
-        save_path = save_path.trim().to_string();
+        let save_path = read_input();
 
         println!("enter load file path:");
-        let mut load_path = String::new();
-        std::io::stdin().read_line(&mut load_path);
-        load_path = load_path.trim().to_string();
+        let load_path = read_input();
+
+        // a loaded network keeps its encoder, action space and layers
+        let mut encoder = String::new();
+        let mut action_space = String::new();
+        let mut layout = String::new();
+        if load_path.is_empty() {
+            println!(
+                "enter observation encoder, empty for {} (one or more of {} joined with +):",
+                DEFAULT_ENCODER,
+                ENCODER_NAMES.join(", ")
+            );
+            encoder = read_input();
+
+            println!("enter action space, empty for absolute (absolute, relative):");
+            action_space = read_input();
+
+            println!(
+                "enter hidden layer sizes, empty for {} (each one can end with :activation, add output:activation for the output layer, activations are {}):",
+                DEFAULT_LAYOUT,
+                get_activation_names().join(", ")
+            );
+            layout = read_input();
+        }
+        let encoder = match encoder.trim() {
+            "" => DEFAULT_ENCODER,
+            encoder => encoder,
+        };
+        let layout = match layout.trim() {
+            "" => DEFAULT_LAYOUT,
+            layout => layout,
+        };
+        let action_space = match action_space.trim() {
+            "" => ActionSpace::Absolute,
+            name => match ActionSpace::from_name(name) {
+                Some(action_space) => action_space,
+                None => {
+                    println!("unknown action space {}", name);
+                    return;
+                }
+            },
+        };
 
         println!("staring training...\npress ctrl+c at any time to stop the training\nthe best network will be saved in the save folder with the name \"best.bin\"");
-        train_network(&save_path, &load_path);
+        if let Err(error) = train_network(&save_path, &load_path, encoder, action_space, layout) {
+            println!("{}", error);
+        }
+    } else if option.eq("4") {
+        println!(
+            "enter network path or agent name ({}):",
+            AGENT_NAMES.join(", ")
+        );
+        let agent_name = read_input();
+
+        println!("enter amount of games:");
+        let games = read_input();
+
+        if let Err(error) = eval(&[agent_name, "--games".to_string(), games]) {
+            println!("{}", error);
+        }
+    } else if option.eq("5") {
+        println!("enter replay path:");
+        watch_replay(&read_input(), &settings);
+    } else if option.eq("6") {
+        if let Err(error) = show_high_scores(&[]) {
+            println!("{}", error);
+        }
     } else {
         println!("Invalid option");
     }
@@ -0,0 +1,108 @@
+pub mod network_view {
+    use ndarray::Array1;
+    use opengl_graphics::GlGraphics;
+    use piston::RenderArgs;
+
+    use crate::{
+        agents::neural_agent::neural_agent::get_choice,
+        ai::neural_network::neural_network::NeuralNetwork,
+        theme::theme::{Color, Theme},
+    };
+
+    const PADDING: f64 = 20.0;
+    const MAX_NODE_RADIUS: f64 = 10.0;
+
+    fn lerp(from: Color, to: Color, amount: f32) -> Color {
+        let mut color = from;
+
+        for (channel, to) in color.iter_mut().zip(to) {
+            *channel += (to - *channel) * amount;
+        }
+
+        color
+    }
+
+    // draws the network in `area` (x, y, width, height) as columns of nodes from the input on
+    // the left to the output on the right. edges get their color from the sign of their
+    // weight and their thickness from its size compared to the rest of the layer, nodes are
+    // brighter the bigger their activation (from 0 to 1, values of functions like tanh or relu
+    // are clamped) and the chosen output has a ring around it
+    pub fn render_network(
+        args: &RenderArgs,
+        gl: &mut GlGraphics,
+        area: [f64; 4],
+        network: &NeuralNetwork,
+        activations: &[Array1<f64>],
+        theme: &Theme,
+    ) {
+        use graphics::*;
+
+        let [x, y, width, height] = area;
+        let column_step = (width - PADDING * 2.0) / (activations.len() - 1).max(1) as f64;
+
+        let get_node_step =
+            |layer: usize| (height - PADDING * 2.0) / activations[layer].len() as f64;
+        let get_node = |layer: usize, node: usize| {
+            [
+                x + PADDING + layer as f64 * column_step,
+                y + PADDING + (node as f64 + 0.5) * get_node_step(layer),
+            ]
+        };
+
+        let weights = network.get_weights();
+        let output_layer = activations.len() - 1;
+        let choice = get_choice(&activations[output_layer]);
+
+        gl.draw(args.viewport(), |context, gl| {
+            for (layer, weights) in weights.iter().enumerate() {
+                let max_weight = weights
+                    .iter()
+                    .fold(0.0, |max: f64, weight| max.max(weight.abs()));
+
+                if max_weight == 0.0 {
+                    continue;
+                }
+
+                for ((output, input), weight) in weights.indexed_iter() {
+                    let strength = weight.abs() / max_weight;
+                    let mut color = if *weight > 0.0 {
+                        theme.weight_positive
+                    } else {
+                        theme.weight_negative
+                    };
+                    color[3] *= strength as f32 * 0.6;
+
+                    line_from_to(
+                        color,
+                        0.2 + strength,
+                        get_node(layer, input),
+                        get_node(layer + 1, output),
+                        context.transform,
+                        gl,
+                    );
+                }
+            }
+
+            for (layer, values) in activations.iter().enumerate() {
+                let radius = (get_node_step(layer) * 0.35).min(MAX_NODE_RADIUS);
+
+                for (node, value) in values.iter().enumerate() {
+                    let [node_x, node_y] = get_node(layer, node);
+                    let circle = ellipse::circle(node_x, node_y, radius);
+                    let color = lerp(theme.node_off, theme.node_on, value.clamp(0.0, 1.0) as f32);
+
+                    ellipse(color, circle, context.transform, gl);
+
+                    if layer == output_layer && node == choice {
+                        Ellipse::new_border(theme.head, 2.0).draw(
+                            ellipse::circle(node_x, node_y, radius + 4.0),
+                            &context.draw_state,
+                            context.transform,
+                            gl,
+                        );
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,254 @@
+pub mod replay {
+    use std::io::{Error, ErrorKind, Read, Seek, Write};
+
+    use opengl_graphics::GlGraphics;
+
+    use crate::{
+        constants::constants::{MAX_GRID_SIZE, MIN_GRID_SIZE},
+        game::game::{Direction, Game, GameConfig},
+    };
+
+    // file layout, all numbers little endian:
+    //   "SNKR" version:u8 seed:u64 grid_size:u32 max_turns_without_apple:u32 max_turns:u32
+    //   action_count:u32 actions packed 4 per byte (2 bits each, index into Direction::ALL)
+    // a limit of 0 means the game had no limit
+    const MAGIC: &[u8; 4] = b"SNKR";
+    const VERSION: u8 = 1;
+
+    #[derive(Debug, Clone)]
+    pub struct Replay {
+        pub seed: u64,
+        pub config: GameConfig,
+        pub actions: Vec<Direction>,
+    }
+
+    fn read_u32(file: &mut std::fs::File) -> std::io::Result<u32> {
+        let mut buf = [0; 4];
+        file.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn get_action_index(action: Direction) -> u8 {
+        Direction::ALL
+            .iter()
+            .position(|direction| *direction == action)
+            .unwrap() as u8
+    }
+
+    // true if the file starts like a replay, whatever its version
+    pub fn is_replay_file(path: &str) -> bool {
+        let mut magic = [0; 4];
+
+        std::fs::File::open(path)
+            .and_then(|mut file| file.read_exact(&mut magic))
+            .is_ok_and(|_| &magic == MAGIC)
+    }
+
+    impl Replay {
+        pub fn write_to_file(&self, path: &str) -> std::io::Result<()> {
+            let mut file = std::fs::File::create(path)?;
+
+            file.write_all(MAGIC)?;
+            file.write_all(&[VERSION])?;
+            file.write_all(&self.seed.to_le_bytes())?;
+            file.write_all(&self.config.grid_size.to_le_bytes())?;
+            file.write_all(
+                &self
+                    .config
+                    .max_turns_without_apple
+                    .unwrap_or(0)
+                    .to_le_bytes(),
+            )?;
+            file.write_all(&self.config.max_turns.unwrap_or(0).to_le_bytes())?;
+            file.write_all(&(self.actions.len() as u32).to_le_bytes())?;
+
+            let packed: Vec<u8> = self
+                .actions
+                .chunks(4)
+                .map(|chunk| {
+                    chunk.iter().enumerate().fold(0, |byte, (i, action)| {
+                        byte | get_action_index(*action) << (i * 2)
+                    })
+                })
+                .collect();
+
+            file.write_all(&packed)
+        }
+
+        pub fn new_from_file(path: &str) -> std::io::Result<Replay> {
+            let mut file = std::fs::File::open(path)?;
+
+            let mut header = [0; 5];
+            file.read_exact(&mut header)?;
+
+            if &header[..4] != MAGIC {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{} is not a replay file", path),
+                ));
+            }
+
+            if header[4] != VERSION {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "{} is a version {} replay, only version {} can be played back",
+                        path, header[4], VERSION
+                    ),
+                ));
+            }
+
+            let mut seed = [0; 8];
+            file.read_exact(&mut seed)?;
+
+            let grid_size = read_u32(&mut file)?;
+            let max_turns_without_apple = read_u32(&mut file)?;
+            let max_turns = read_u32(&mut file)?;
+            let action_count = read_u32(&mut file)? as usize;
+
+            if !(MIN_GRID_SIZE..=MAX_GRID_SIZE).contains(&grid_size) {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{} has a {}x{} board", path, grid_size, grid_size),
+                ));
+            }
+
+            // checked before allocating so a broken count can't ask for gigabytes
+            let remaining = file.metadata()?.len() - file.stream_position()?;
+            if action_count.div_ceil(4) as u64 > remaining {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{} is missing some of its {} moves", path, action_count),
+                ));
+            }
+
+            let mut packed = vec![0; action_count.div_ceil(4)];
+            file.read_exact(&mut packed)?;
+
+            let actions = (0..action_count)
+                .map(|i| Direction::ALL[(packed[i / 4] >> ((i % 4) * 2) & 0b11) as usize])
+                .collect();
+
+            Ok(Replay {
+                seed: u64::from_le_bytes(seed),
+                config: GameConfig {
+                    grid_size,
+                    max_turns_without_apple: (max_turns_without_apple != 0)
+                        .then_some(max_turns_without_apple),
+                    max_turns: (max_turns != 0).then_some(max_turns),
+                },
+                actions,
+            })
+        }
+
+        // the game as it was after `tick` moves
+        pub fn create_game(&self, gl: Option<GlGraphics>, tick: usize) -> Game {
+            let mut game = Game::new_with(gl, self.config, self.seed);
+
+            for action in self.actions.iter().take(tick) {
+                game.set_direction(*action);
+                game.tick();
+            }
+
+            game
+        }
+
+        pub fn get_tick_count(&self) -> usize {
+            self.actions.len()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::test_utils::test_utils::get_temp_path;
+
+        #[test]
+        fn round_trip() {
+            // 7 actions leave the last byte half full
+            let replay = Replay {
+                seed: 42,
+                config: GameConfig {
+                    grid_size: 10,
+                    max_turns_without_apple: Some(150),
+                    max_turns: None,
+                },
+                actions: vec![
+                    Direction::Up,
+                    Direction::Left,
+                    Direction::Down,
+                    Direction::Down,
+                    Direction::Right,
+                    Direction::Up,
+                    Direction::Left,
+                ],
+            };
+            let path = get_temp_path("replay");
+
+            replay.write_to_file(&path).unwrap();
+            let loaded = Replay::new_from_file(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            let loaded = loaded.unwrap();
+            assert_eq!(loaded.seed, replay.seed);
+            assert_eq!(loaded.config.grid_size, 10);
+            assert_eq!(loaded.config.max_turns_without_apple, Some(150));
+            assert_eq!(loaded.config.max_turns, None);
+            assert_eq!(loaded.actions, replay.actions);
+        }
+
+        // a replay file with a header and `packed_size` bytes of moves
+        fn read_replay_bytes(
+            name: &str,
+            version: u8,
+            grid_size: u32,
+            action_count: u32,
+            packed_size: usize,
+        ) -> std::io::Result<Replay> {
+            let mut bytes = MAGIC.to_vec();
+            bytes.push(version);
+            bytes.extend(7u64.to_le_bytes());
+            for value in [grid_size, 0, 0, action_count] {
+                bytes.extend(value.to_le_bytes());
+            }
+            bytes.extend(vec![0; packed_size]);
+
+            let path = get_temp_path(name);
+            std::fs::write(&path, bytes).unwrap();
+            let loaded = Replay::new_from_file(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            loaded
+        }
+
+        #[test]
+        fn rejects_other_files() {
+            let path = get_temp_path("not-replay");
+
+            std::fs::write(&path, b"PNG image").unwrap();
+            let loaded = Replay::new_from_file(&path);
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.unwrap_err().kind(), ErrorKind::InvalidData);
+
+            let loaded = read_replay_bytes("replay-version", VERSION + 1, 10, 4, 1);
+            assert_eq!(loaded.unwrap_err().kind(), ErrorKind::InvalidData);
+        }
+
+        #[test]
+        fn rejects_bad_headers() {
+            assert!(read_replay_bytes("replay-ok", VERSION, 10, 5, 2).is_ok());
+
+            for grid_size in [MIN_GRID_SIZE - 1, MAX_GRID_SIZE + 1, 70000] {
+                let loaded = read_replay_bytes("replay-grid", VERSION, grid_size, 4, 1);
+                assert_eq!(loaded.unwrap_err().kind(), ErrorKind::InvalidData);
+            }
+
+            // 5 moves need 2 bytes
+            let loaded = read_replay_bytes("replay-short", VERSION, 10, 5, 1);
+            assert_eq!(loaded.unwrap_err().kind(), ErrorKind::InvalidData);
+            let loaded = read_replay_bytes("replay-huge", VERSION, 10, u32::MAX, 1);
+            assert_eq!(loaded.unwrap_err().kind(), ErrorKind::InvalidData);
+        }
+    }
+}
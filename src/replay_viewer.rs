@@ -0,0 +1,180 @@
+pub mod replay_viewer {
+    use crate::{
+        constants::constants::{MAX_SPEED, MIN_SPEED, TIME_BETWEEN_MOVES},
+        game::game::Game,
+        key_bindings::key_bindings::Command,
+        replay::replay::Replay,
+        theme::theme::Theme,
+    };
+    use opengl_graphics::GlGraphics;
+    use piston::{Key, RenderArgs, UpdateArgs};
+
+    // pause: pause / resume, right / left: step one tick forward / backward,
+    // up / down or speed up / down: double / halve the speed, restart or home: jump to the start,
+    // end: jump to the end, type a tick number and press enter to jump to it
+    pub struct ReplayViewer {
+        replay: Replay,
+        game: Game,
+        tick: usize,
+        paused: bool,
+        speed: f64,
+        time_to_move: f64,
+        jump_input: String,
+    }
+
+    impl ReplayViewer {
+        pub fn new(gl: Option<GlGraphics>, replay: Replay) -> ReplayViewer {
+            ReplayViewer {
+                game: replay.create_game(gl, 0),
+                replay,
+                tick: 0,
+                paused: false,
+                speed: 1.0,
+                time_to_move: TIME_BETWEEN_MOVES,
+                jump_input: String::new(),
+            }
+        }
+
+        // the game doesn't run its own timer here, so the progress comes from the viewer's
+        pub fn render(&mut self, args: &RenderArgs, theme: &Theme, smooth: bool) {
+            let progress = if smooth && !self.paused && self.tick < self.replay.get_tick_count() {
+                (1.0 - self.time_to_move / (TIME_BETWEEN_MOVES / self.speed)).clamp(0.0, 1.0)
+            } else {
+                1.0
+            };
+
+            self.game.render(args, theme, progress);
+        }
+
+        pub fn handle_command(&mut self, command: Command) {
+            match command {
+                Command::Pause => self.paused = !self.paused,
+                Command::Right => {
+                    self.paused = true;
+                    self.step_forward();
+                }
+                Command::Left => {
+                    self.paused = true;
+                    self.jump_to(self.tick.saturating_sub(1));
+                }
+                Command::Up | Command::SpeedUp => self.speed = (self.speed * 2.0).min(MAX_SPEED),
+                Command::Down | Command::SpeedDown => {
+                    self.speed = (self.speed / 2.0).max(MIN_SPEED)
+                }
+                Command::Restart => self.jump_to(0),
+                Command::Turbo
+                | Command::Sensors
+                | Command::Network
+                | Command::Fullscreen
+                | Command::Quit => {}
+            }
+        }
+
+        // keys that aren't bound to a command
+        pub fn handle_key(&mut self, key: Key) {
+            match key {
+                Key::Home => self.jump_to(0),
+                Key::End => self.jump_to(self.replay.get_tick_count()),
+                Key::Return => {
+                    if let Ok(tick) = self.jump_input.parse() {
+                        self.jump_to(tick);
+                    }
+                    self.jump_input.clear();
+                }
+                Key::Backspace => {
+                    self.jump_input.pop();
+                }
+                _ => {
+                    let digit = match key {
+                        Key::D0 | Key::NumPad0 => '0',
+                        Key::D1 | Key::NumPad1 => '1',
+                        Key::D2 | Key::NumPad2 => '2',
+                        Key::D3 | Key::NumPad3 => '3',
+                        Key::D4 | Key::NumPad4 => '4',
+                        Key::D5 | Key::NumPad5 => '5',
+                        Key::D6 | Key::NumPad6 => '6',
+                        Key::D7 | Key::NumPad7 => '7',
+                        Key::D8 | Key::NumPad8 => '8',
+                        Key::D9 | Key::NumPad9 => '9',
+                        _ => return,
+                    };
+                    self.jump_input.push(digit);
+                }
+            }
+        }
+
+        pub fn update(&mut self, args: &UpdateArgs) {
+            if self.paused {
+                return;
+            }
+
+            self.time_to_move -= args.dt;
+
+            while self.time_to_move <= 0.0 {
+                self.time_to_move += TIME_BETWEEN_MOVES / self.speed;
+                self.step_forward();
+            }
+        }
+
+        fn step_forward(&mut self) {
+            if self.tick >= self.replay.get_tick_count() {
+                return;
+            }
+
+            self.game.set_direction(self.replay.actions[self.tick]);
+            self.game.tick();
+            self.tick += 1;
+        }
+
+        // the game can only move forward, so jumping back replays it from the start
+        fn jump_to(&mut self, tick: usize) {
+            let tick = tick.min(self.replay.get_tick_count());
+
+            if tick < self.tick {
+                let gl = self.game.gl.take();
+                self.game = self.replay.create_game(gl, tick);
+                self.tick = tick;
+            }
+
+            while self.tick < tick {
+                self.step_forward();
+            }
+        }
+
+        pub fn get_game(&self) -> &Game {
+            &self.game
+        }
+
+        pub fn get_game_mut(&mut self) -> &mut Game {
+            &mut self.game
+        }
+
+        pub fn get_speed(&self) -> f64 {
+            self.speed
+        }
+
+        pub fn get_title(&self) -> String {
+            let mut title = format!(
+                "replay - tick {}/{} - score {} - speed x{}",
+                self.tick,
+                self.replay.get_tick_count(),
+                self.game.get_score(),
+                self.speed
+            );
+
+            if self.paused {
+                title += " - paused";
+            }
+
+            if let Some(terminal_state) = self.game.get_terminal_state() {
+                title += &format!(" - {}", terminal_state.get_name());
+            }
+
+            if !self.jump_input.is_empty() {
+                title += &format!(" - jump to tick {}", self.jump_input);
+            }
+
+            title
+        }
+    }
+}
@@ -0,0 +1,107 @@
+pub mod sensors {
+    use ndarray::Array1;
+    use opengl_graphics::GlGraphics;
+    use piston::RenderArgs;
+
+    use crate::{
+        agents::neural_agent::neural_agent::get_choice,
+        ai::{actions::actions::ActionSpace, observation::observation::Ray},
+        game::game::GameState,
+        theme::theme::{Color, Theme},
+    };
+
+    // draws the rays the network sees from the head, each one goes to the wall and has a dot
+    // on the first body part and on the apple it saw
+    pub fn render_rays(
+        args: &RenderArgs,
+        gl: &mut GlGraphics,
+        (origin, cell_size): ([f64; 2], f64),
+        state: &GameState,
+        rays: &[Ray],
+        theme: &Theme,
+    ) {
+        use graphics::*;
+
+        let head = state.get_head();
+
+        // the center of the cell `distance` cells away from the head along `direction`
+        let get_point = |direction: (i32, i32), distance: f64| {
+            [
+                origin[0] + (head.0 as f64 + 0.5 + direction.0 as f64 * distance) * cell_size,
+                origin[1] + (head.1 as f64 + 0.5 + direction.1 as f64 * distance) * cell_size,
+            ]
+        };
+
+        gl.draw(args.viewport(), |context, gl| {
+            let dot = |color: Color, point: [f64; 2], radius: f64, gl: &mut GlGraphics| {
+                ellipse(
+                    color,
+                    ellipse::circle(point[0], point[1], cell_size * radius),
+                    context.transform,
+                    gl,
+                );
+            };
+
+            for ray in rays {
+                // the wall is half a cell after the last cell of the board
+                let wall = get_point(ray.direction, ray.wall_distance as f64 - 0.5);
+
+                line_from_to(
+                    theme.ray,
+                    cell_size * 0.05,
+                    get_point(ray.direction, 0.0),
+                    wall,
+                    context.transform,
+                    gl,
+                );
+
+                dot(theme.ray_wall, wall, 0.12, gl);
+
+                if ray.snake_distance > 0 {
+                    dot(
+                        theme.ray_body,
+                        get_point(ray.direction, ray.snake_distance as f64),
+                        0.2,
+                        gl,
+                    );
+                }
+
+                if ray.food_distance > 0 {
+                    dot(
+                        theme.ray_apple,
+                        get_point(ray.direction, ray.food_distance as f64),
+                        0.2,
+                        gl,
+                    );
+                }
+            }
+        });
+    }
+
+    // the name of every action with the network's output for it
+    pub fn get_output_bars(
+        outputs: &Array1<f64>,
+        action_space: ActionSpace,
+    ) -> Vec<(&'static str, f64)> {
+        action_space
+            .get_output_names()
+            .iter()
+            .zip(outputs.iter())
+            .map(|(name, output)| (*name, *output))
+            .collect()
+    }
+
+    // "up 0.91 right 0.12 down 0.05 left 0.30 -> up", for the terminal
+    pub fn get_outputs_text(outputs: &Array1<f64>, action_space: ActionSpace) -> String {
+        let values: Vec<String> = get_output_bars(outputs, action_space)
+            .iter()
+            .map(|(name, output)| format!("{} {:.2}", name, output))
+            .collect();
+
+        format!(
+            "{} -> {}",
+            values.join(" "),
+            action_space.get_output_names()[get_choice(outputs)]
+        )
+    }
+}
@@ -1,35 +1,114 @@
 pub mod snake {
-    use crate::constants::constants::*;
+    use crate::{game::game::TerminalState, theme::theme::Theme};
     use opengl_graphics::GlGraphics;
     use piston::RenderArgs;
 
     pub struct Snake {
         snake: Vec<(i32, i32)>,
+        // the cells before the last move, used to draw the snake between two moves
+        previous: Vec<(i32, i32)>,
         direction: (i32, i32),
+        grid_size: u32,
     }
 
     impl Snake {
-        pub fn new() -> Snake {
+        pub fn new(grid_size: u32) -> Snake {
+            let snake = vec![(3, (grid_size / 2) as i32), (2, (grid_size / 2) as i32)];
+
             Snake {
-                snake: vec![(3, (GRID_SIZE / 2) as i32), (2, (GRID_SIZE / 2) as i32)],
+                previous: snake.clone(),
+                snake,
                 direction: (1, 0),
+                grid_size,
             }
         }
 
-        pub fn render(&mut self, args: &RenderArgs, gl: &mut GlGraphics) {
+        // the snake is a band a bit thinner than a cell going from the head through the cells
+        // of the previous move to the tail, and the head has eyes looking where it is going.
+        // `progress` is how far the snake is between its previous and current cells, from 0
+        // to 1, the game itself only knows whole cells
+        pub fn render(
+            &mut self,
+            args: &RenderArgs,
+            gl: &mut GlGraphics,
+            origin: [f64; 2],
+            cell_size: f64,
+            theme: &Theme,
+            progress: f64,
+        ) {
             use graphics::*;
 
-            const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
-
-            let square = rectangle::square(0.0, 0.0, SNAKE_SIZE);
+            let margin = cell_size * 0.1;
+            let size = cell_size - margin * 2.0;
+            let length = self.snake.len();
+
+            // a part added by eating an apple didn't exist before the move and stays in place
+            let get_previous = |i: usize| self.previous[i.min(self.previous.len() - 1)];
+
+            let positions: Vec<[f64; 2]> = self
+                .snake
+                .iter()
+                .enumerate()
+                .map(|(i, part)| {
+                    let previous = get_previous(i);
+                    [
+                        previous.0 as f64 + (part.0 - previous.0) as f64 * progress,
+                        previous.1 as f64 + (part.1 - previous.1) as f64 * progress,
+                    ]
+                })
+                .collect();
+
+            let to_cell = |part: (i32, i32)| [part.0 as f64, part.1 as f64];
+
+            // the rectangle covering a part at `from`, a part at `to` and everything between,
+            // the two have to be on the same row or column
+            let band = |from: [f64; 2], to: [f64; 2]| {
+                [
+                    origin[0] + from[0].min(to[0]) * cell_size + margin,
+                    origin[1] + from[1].min(to[1]) * cell_size + margin,
+                    (from[0] - to[0]).abs() * cell_size + size,
+                    (from[1] - to[1]).abs() * cell_size + size,
+                ]
+            };
 
             gl.draw(args.viewport(), |context, gl| {
-                for rec in self.snake.iter() {
-                    let transform = context
-                        .transform
-                        .trans(rec.0 as f64 * SNAKE_SIZE, rec.1 as f64 * SNAKE_SIZE);
+                // from the tail so the head is drawn on top
+                for i in (0..length).rev() {
+                    if i == 0 {
+                        rectangle(
+                            theme.head,
+                            band(positions[0], positions[0]),
+                            context.transform,
+                            gl,
+                        );
+                        continue;
+                    }
+
+                    let color = theme.get_body_color(i, length);
+
+                    // the part follows the one in front of it through the cell that one was in
+                    let corner = to_cell(get_previous(i - 1));
+                    rectangle(color, band(positions[i], corner), context.transform, gl);
+                    rectangle(color, band(corner, positions[i - 1]), context.transform, gl);
+                }
 
-                    rectangle(GREEN, square, transform, gl);
+                let center = [
+                    origin[0] + (positions[0][0] + 0.5) * cell_size,
+                    origin[1] + (positions[0][1] + 0.5) * cell_size,
+                ];
+                let forward = [self.direction.0 as f64, self.direction.1 as f64];
+                let side = [-forward[1], forward[0]];
+
+                for sign in [-1.0, 1.0] {
+                    let eye_x = center[0] + (forward[0] * 0.15 + side[0] * sign * 0.2) * cell_size;
+                    let eye_y = center[1] + (forward[1] * 0.15 + side[1] * sign * 0.2) * cell_size;
+
+                    ellipse(
+                        theme.eyes,
+                        ellipse::circle(eye_x, eye_y, cell_size * 0.09),
+                        context.transform,
+                        gl,
+                    );
                 }
             });
         }
@@ -42,6 +121,8 @@ pub mod snake {
         }
 
         pub fn update(&mut self, extend: bool) {
+            self.previous.clone_from(&self.snake);
+
             if extend {
                 self.snake.push(self.snake[self.snake.len() - 1]);
             }
@@ -69,21 +150,22 @@ pub mod snake {
             self.snake[0]
         }
 
-        pub fn is_dead(&self) -> bool {
-            let head = self.snake[0];
+        pub fn is_inside(&self, cell: (i32, i32)) -> bool {
+            let grid_size = self.grid_size as i32;
+            cell.0 >= 0 && cell.0 < grid_size && cell.1 >= 0 && cell.1 < grid_size
+        }
 
-            if head.0 < 0 || head.0 >= GRID_SIZE as i32 || head.1 < 0 || head.1 >= GRID_SIZE as i32
-            {
-                return true;
-            }
+        // the wall is checked before the snake moves, see Game::tick
+        pub fn get_collision(&self) -> Option<TerminalState> {
+            let head = self.snake[0];
 
             for rec in self.snake.iter().skip(1) {
                 if rec.0 == head.0 && rec.1 == head.1 {
-                    return true;
+                    return Some(TerminalState::HitSelf);
                 }
             }
 
-            false
+            None
         }
 
         pub fn get_size(&self) -> usize {
@@ -0,0 +1,345 @@
+pub mod terminal {
+    use std::io::{stdout, Stdout, Write};
+    use std::time::{Duration, Instant};
+
+    use crossterm::{
+        cursor,
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute, queue,
+        style::{self, Color},
+        terminal,
+    };
+    use piston::{Key, UpdateArgs};
+
+    use crate::{
+        ai::ai_game::ai_game::AiGame,
+        game::game::{Game, MapItem},
+        high_scores::high_scores::ScoreKeeper,
+        hud::hud::{get_ai_overlay, get_overlay, get_stats},
+        key_bindings::key_bindings::{Command, KeyBindings},
+        replay_viewer::replay_viewer::ReplayViewer,
+        sensors::sensors::get_outputs_text,
+    };
+
+    const FRAME_TIME: Duration = Duration::from_millis(16);
+
+    const BACKGROUND: Color = Color::Rgb {
+        r: 30,
+        g: 30,
+        b: 30,
+    };
+    const BORDER: Color = Color::Rgb {
+        r: 90,
+        g: 90,
+        b: 90,
+    };
+    const HEAD: Color = Color::Rgb { r: 0, g: 150, b: 0 };
+    const BODY: Color = Color::Rgb { r: 0, g: 220, b: 0 };
+    const APPLE: Color = Color::Rgb { r: 220, g: 0, b: 0 };
+
+    // anything that can be driven and drawn by the terminal front-end
+    pub trait TerminalView {
+        fn handle_command(&mut self, command: Command);
+        fn update(&mut self, args: &UpdateArgs);
+        fn get_game(&self) -> &Game;
+
+        // keys that aren't bound to a command
+        fn handle_key(&mut self, _key: Key) {}
+
+        fn get_status(&self, _key_bindings: &KeyBindings) -> String {
+            String::new()
+        }
+    }
+
+    impl TerminalView for Game {
+        fn handle_command(&mut self, command: Command) {
+            Game::handle_command(self, command);
+        }
+
+        fn update(&mut self, args: &UpdateArgs) {
+            Game::update(self, args);
+        }
+
+        fn get_game(&self) -> &Game {
+            self
+        }
+
+        fn get_status(&self, key_bindings: &KeyBindings) -> String {
+            get_overlay(self, key_bindings)
+                .map(|lines| lines.join(", "))
+                .unwrap_or_default()
+        }
+    }
+
+    impl TerminalView for AiGame {
+        fn handle_command(&mut self, command: Command) {
+            AiGame::handle_command(self, command);
+        }
+
+        fn update(&mut self, args: &UpdateArgs) {
+            AiGame::update(self, args);
+        }
+
+        fn get_game(&self) -> &Game {
+            AiGame::get_game(self)
+        }
+
+        fn get_status(&self, key_bindings: &KeyBindings) -> String {
+            let mut status = get_ai_overlay(self.get_game(), key_bindings).unwrap_or_default();
+
+            if self.is_turbo() {
+                status.insert(0, "turbo".to_string());
+            }
+
+            if self.is_showing_sensors() {
+                let action_space = self.get_agent().get_action_space();
+                status.push(get_outputs_text(&self.get_outputs(), action_space));
+            }
+
+            status.join(", ")
+        }
+    }
+
+    impl TerminalView for ReplayViewer {
+        fn handle_command(&mut self, command: Command) {
+            ReplayViewer::handle_command(self, command);
+        }
+
+        fn handle_key(&mut self, key: Key) {
+            ReplayViewer::handle_key(self, key);
+        }
+
+        fn update(&mut self, args: &UpdateArgs) {
+            ReplayViewer::update(self, args);
+        }
+
+        fn get_game(&self) -> &Game {
+            ReplayViewer::get_game(self)
+        }
+
+        fn get_status(&self, _key_bindings: &KeyBindings) -> String {
+            self.get_title()
+        }
+    }
+
+    // records the games of another view in the high scores
+    pub struct ScoredView<V: TerminalView> {
+        pub view: V,
+        pub keeper: ScoreKeeper,
+    }
+
+    impl<V: TerminalView> TerminalView for ScoredView<V> {
+        fn handle_command(&mut self, command: Command) {
+            self.view.handle_command(command);
+        }
+
+        fn handle_key(&mut self, key: Key) {
+            self.view.handle_key(key);
+        }
+
+        fn update(&mut self, args: &UpdateArgs) {
+            self.view.update(args);
+            self.keeper.update(self.view.get_game());
+        }
+
+        fn get_game(&self) -> &Game {
+            self.view.get_game()
+        }
+
+        // only the rank fits in the status line, the table is printed when the terminal closes
+        fn get_status(&self, key_bindings: &KeyBindings) -> String {
+            let mut status = vec![self.view.get_status(key_bindings)];
+            status.extend(self.keeper.get_lines().first().cloned());
+            status.retain(|part| !part.is_empty());
+
+            status.join(", ")
+        }
+    }
+
+    // terminal keys are translated to piston keys so both front-ends share the key bindings
+    fn to_piston_key(code: KeyCode) -> Option<Key> {
+        let key = match code {
+            KeyCode::Up => Key::Up,
+            KeyCode::Down => Key::Down,
+            KeyCode::Left => Key::Left,
+            KeyCode::Right => Key::Right,
+            KeyCode::Enter => Key::Return,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Home => Key::Home,
+            KeyCode::End => Key::End,
+            KeyCode::PageUp => Key::PageUp,
+            KeyCode::PageDown => Key::PageDown,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Esc => Key::Escape,
+            KeyCode::F(11) => Key::F11,
+            KeyCode::Char(' ') => Key::Space,
+            KeyCode::Char(c) => match c.to_ascii_lowercase() {
+                '0' => Key::D0,
+                '1' => Key::D1,
+                '2' => Key::D2,
+                '3' => Key::D3,
+                '4' => Key::D4,
+                '5' => Key::D5,
+                '6' => Key::D6,
+                '7' => Key::D7,
+                '8' => Key::D8,
+                '9' => Key::D9,
+                'a' => Key::A,
+                'b' => Key::B,
+                'c' => Key::C,
+                'd' => Key::D,
+                'e' => Key::E,
+                'f' => Key::F,
+                'g' => Key::G,
+                'h' => Key::H,
+                'i' => Key::I,
+                'j' => Key::J,
+                'k' => Key::K,
+                'l' => Key::L,
+                'm' => Key::M,
+                'n' => Key::N,
+                'o' => Key::O,
+                'p' => Key::P,
+                'q' => Key::Q,
+                'r' => Key::R,
+                's' => Key::S,
+                't' => Key::T,
+                'u' => Key::U,
+                'v' => Key::V,
+                'w' => Key::W,
+                'x' => Key::X,
+                'y' => Key::Y,
+                'z' => Key::Z,
+                '+' | '=' => Key::Equals,
+                '-' => Key::Minus,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(key)
+    }
+
+    // ctrl+c always quits, whatever the key bindings are
+    fn is_interrupt(key_event: &KeyEvent) -> bool {
+        key_event.code == KeyCode::Char('c') && key_event.modifiers.contains(KeyModifiers::CONTROL)
+    }
+
+    // puts the terminal in raw mode on an alternate screen and restores it when dropped,
+    // even if the game panics
+    struct TerminalGuard;
+
+    impl TerminalGuard {
+        fn new() -> crossterm::Result<TerminalGuard> {
+            terminal::enable_raw_mode()?;
+            execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+            Ok(TerminalGuard)
+        }
+    }
+
+    impl Drop for TerminalGuard {
+        fn drop(&mut self) {
+            let _ = execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    fn draw(
+        out: &mut Stdout,
+        view: &dyn TerminalView,
+        key_bindings: &KeyBindings,
+    ) -> crossterm::Result<()> {
+        let game = view.get_game();
+        let state = game.get_state();
+        let grid_size = state.grid_size as i32;
+
+        queue!(out, cursor::MoveTo(0, 0), style::ResetColor)?;
+
+        queue!(
+            out,
+            style::Print(get_stats(game)),
+            terminal::Clear(terminal::ClearType::UntilNewLine),
+            cursor::MoveToNextLine(1),
+            style::Print(view.get_status(key_bindings)),
+            terminal::Clear(terminal::ClearType::UntilNewLine),
+            cursor::MoveToNextLine(1),
+        )?;
+
+        let mut map = vec![vec![MapItem::Empty; grid_size as usize]; grid_size as usize];
+
+        for (x, y) in &state.snake {
+            map[*y as usize][*x as usize] = MapItem::Snake;
+        }
+
+        map[state.apple.1 as usize][state.apple.0 as usize] = MapItem::Apple;
+
+        let head = state.get_head();
+
+        for y in -1..=grid_size {
+            for x in -1..=grid_size {
+                let color = if x < 0 || y < 0 || x == grid_size || y == grid_size {
+                    BORDER
+                } else if (x, y) == head {
+                    HEAD
+                } else {
+                    match map[y as usize][x as usize] {
+                        MapItem::Empty => BACKGROUND,
+                        MapItem::Snake => BODY,
+                        MapItem::Apple => APPLE,
+                    }
+                };
+
+                // terminal cells are about twice as tall as they are wide
+                queue!(out, style::SetBackgroundColor(color), style::Print("  "))?;
+            }
+
+            queue!(out, style::ResetColor, cursor::MoveToNextLine(1))?;
+        }
+
+        out.flush()
+    }
+
+    // runs the view in the terminal until a quit key or ctrl+c is pressed
+    pub fn run(view: &mut dyn TerminalView, key_bindings: &KeyBindings) -> crossterm::Result<()> {
+        let _guard = TerminalGuard::new()?;
+        let mut out = stdout();
+        execute!(out, terminal::Clear(terminal::ClearType::All))?;
+
+        let mut last_update = Instant::now();
+
+        loop {
+            while event::poll(Duration::ZERO)? {
+                if let Event::Key(key_event) = event::read()? {
+                    if key_event.kind == KeyEventKind::Release {
+                        continue;
+                    }
+
+                    if is_interrupt(&key_event) {
+                        return Ok(());
+                    }
+
+                    let key = match to_piston_key(key_event.code) {
+                        Some(key) => key,
+                        None => continue,
+                    };
+
+                    match key_bindings.get_command(key) {
+                        Some(Command::Quit) => return Ok(()),
+                        Some(command) => view.handle_command(command),
+                        None => view.handle_key(key),
+                    }
+                }
+            }
+
+            let now = Instant::now();
+            view.update(&UpdateArgs {
+                dt: (now - last_update).as_secs_f64(),
+            });
+            last_update = now;
+
+            draw(&mut out, view, key_bindings)?;
+
+            std::thread::sleep(FRAME_TIME);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod test_utils {
+    // a file in the temp folder that only this test run uses, `name` keeps tests apart
+    pub fn get_temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("snake-{}-{}.bin", name, std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+}
@@ -0,0 +1,193 @@
+pub mod theme {
+    use crate::config::config::Config;
+
+    pub type Color = [f32; 4];
+
+    // colors used by the window, a color with 0 alpha is not drawn
+    #[derive(Debug, Clone, Copy)]
+    pub struct Theme {
+        // around the board when the window isn't square
+        pub letterbox: Color,
+        pub background: Color,
+        pub grid: Color,
+        pub head: Color,
+        pub eyes: Color,
+        // the body fades from body_start next to the head to body_end at the tail
+        pub body_start: Color,
+        pub body_end: Color,
+        pub apple: Color,
+        pub text: Color,
+        pub text_background: Color,
+        // the sensor overlay of an ai game: the rays and what they hit
+        pub ray: Color,
+        pub ray_wall: Color,
+        pub ray_body: Color,
+        pub ray_apple: Color,
+        // the network panel of an ai game, nodes go from node_off to node_on with their value
+        pub weight_positive: Color,
+        pub weight_negative: Color,
+        pub node_off: Color,
+        pub node_on: Color,
+    }
+
+    const COLOR_NAMES: [&str; 18] = [
+        "letterbox",
+        "background",
+        "grid",
+        "head",
+        "eyes",
+        "body_start",
+        "body_end",
+        "apple",
+        "text",
+        "text_background",
+        "ray",
+        "ray_wall",
+        "ray_body",
+        "ray_apple",
+        "weight_positive",
+        "weight_negative",
+        "node_off",
+        "node_on",
+    ];
+
+    // "#rrggbb" or "#rrggbbaa"
+    fn parse_color(text: &str) -> Option<Color> {
+        let hex = text.strip_prefix('#')?;
+
+        if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+            return None;
+        }
+
+        let mut color = [1.0; 4];
+
+        for (i, channel) in color.iter_mut().enumerate().take(hex.len() / 2) {
+            *channel = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()? as f32 / 255.0;
+        }
+
+        Some(color)
+    }
+
+    impl Theme {
+        pub fn light() -> Theme {
+            Theme {
+                letterbox: [0.1, 0.1, 0.1, 1.0],
+                background: [1.0, 1.0, 1.0, 1.0],
+                grid: [0.0, 0.0, 0.0, 0.06],
+                head: [0.0, 0.6, 0.0, 1.0],
+                eyes: [1.0, 1.0, 1.0, 1.0],
+                body_start: [0.0, 0.85, 0.0, 1.0],
+                body_end: [0.6, 1.0, 0.6, 1.0],
+                apple: [1.0, 0.0, 0.0, 1.0],
+                text: [0.0, 0.0, 0.0, 1.0],
+                text_background: [1.0, 1.0, 1.0, 0.7],
+                ray: [0.0, 0.0, 0.0, 0.25],
+                ray_wall: [0.3, 0.3, 0.3, 1.0],
+                ray_body: [0.1, 0.2, 0.9, 1.0],
+                ray_apple: [1.0, 0.55, 0.0, 1.0],
+                weight_positive: [0.3, 0.6, 1.0, 1.0],
+                weight_negative: [1.0, 0.4, 0.3, 1.0],
+                node_off: [0.25, 0.25, 0.25, 1.0],
+                node_on: [1.0, 1.0, 1.0, 1.0],
+            }
+        }
+
+        pub fn dark() -> Theme {
+            Theme {
+                letterbox: [0.0, 0.0, 0.0, 1.0],
+                background: [0.12, 0.12, 0.14, 1.0],
+                grid: [1.0, 1.0, 1.0, 0.05],
+                head: [0.3, 0.9, 0.4, 1.0],
+                eyes: [0.05, 0.05, 0.05, 1.0],
+                body_start: [0.2, 0.75, 0.3, 1.0],
+                body_end: [0.05, 0.3, 0.12, 1.0],
+                apple: [0.95, 0.3, 0.3, 1.0],
+                text: [0.9, 0.9, 0.9, 1.0],
+                text_background: [0.0, 0.0, 0.0, 0.6],
+                ray: [1.0, 1.0, 1.0, 0.25],
+                ray_wall: [0.7, 0.7, 0.7, 1.0],
+                ray_body: [0.4, 0.6, 1.0, 1.0],
+                ray_apple: [1.0, 0.75, 0.2, 1.0],
+                weight_positive: [0.35, 0.65, 1.0, 1.0],
+                weight_negative: [1.0, 0.45, 0.35, 1.0],
+                node_off: [0.2, 0.2, 0.22, 1.0],
+                node_on: [0.4, 1.0, 0.5, 1.0],
+            }
+        }
+
+        // `theme = light | dark` picks the base theme and `color.<name> = #rrggbb[aa]`
+        // replaces single colors of it
+        pub fn new_from_config(config: &Config) -> Result<Theme, String> {
+            let mut theme = match config.get("theme") {
+                None | Some("light") => Theme::light(),
+                Some("dark") => Theme::dark(),
+                Some(name) => return Err(format!("unknown theme {}", name)),
+            };
+
+            for name in config.get_names() {
+                if let Some(color_name) = name.strip_prefix("color.") {
+                    if !COLOR_NAMES.contains(&color_name) {
+                        return Err(format!("unknown color in {}", name));
+                    }
+                }
+            }
+
+            for color_name in COLOR_NAMES {
+                let text = match config.get(&format!("color.{}", color_name)) {
+                    Some(text) => text,
+                    None => continue,
+                };
+
+                let color = parse_color(text)
+                    .ok_or(format!("invalid color {} for {}", text, color_name))?;
+
+                *theme.get_color_mut(color_name).unwrap() = color;
+            }
+
+            Ok(theme)
+        }
+
+        fn get_color_mut(&mut self, name: &str) -> Option<&mut Color> {
+            let color = match name {
+                "letterbox" => &mut self.letterbox,
+                "background" => &mut self.background,
+                "grid" => &mut self.grid,
+                "head" => &mut self.head,
+                "eyes" => &mut self.eyes,
+                "body_start" => &mut self.body_start,
+                "body_end" => &mut self.body_end,
+                "apple" => &mut self.apple,
+                "text" => &mut self.text,
+                "text_background" => &mut self.text_background,
+                "ray" => &mut self.ray,
+                "ray_wall" => &mut self.ray_wall,
+                "ray_body" => &mut self.ray_body,
+                "ray_apple" => &mut self.ray_apple,
+                "weight_positive" => &mut self.weight_positive,
+                "weight_negative" => &mut self.weight_negative,
+                "node_off" => &mut self.node_off,
+                "node_on" => &mut self.node_on,
+                _ => return None,
+            };
+
+            Some(color)
+        }
+
+        // color of the body part at `index` (1 is right after the head) of a snake of `length`
+        pub fn get_body_color(&self, index: usize, length: usize) -> Color {
+            let amount = if length <= 2 {
+                0.0
+            } else {
+                (index - 1) as f32 / (length - 2) as f32
+            };
+
+            let mut color = self.body_start;
+
+            for (channel, end) in color.iter_mut().zip(self.body_end) {
+                *channel += (end - *channel) * amount;
+            }
+
+            color
+        }
+    }
+}